@@ -0,0 +1,184 @@
+//! # Comparison of test case results with the results of a previous run
+
+use crate::context::TestResult;
+use crate::errors::RunnerError;
+use antex::{ColorMode, StyledText, Text};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+/// Test case key built from test directory, test file name and test case identifier.
+type TestCaseKey = (String, String, String);
+
+/// Changes of test case results compared to baseline.
+#[derive(Default)]
+pub struct BaselineComparison {
+  /// Test cases that passed in baseline and fail now.
+  pub newly_failing: Vec<TestCaseKey>,
+  /// Test cases that failed in baseline and pass now.
+  pub newly_passing: Vec<TestCaseKey>,
+  /// Test cases not present in baseline, with current result.
+  pub added: Vec<(TestCaseKey, TestResult)>,
+  /// Test cases present in baseline but not executed now.
+  pub removed: Vec<TestCaseKey>,
+}
+
+/// Loads test case results from the TCK report written by the previous run.
+///
+/// Returns `true` for test cases that have passed.
+pub fn load_baseline(file_name: &str) -> Result<BTreeMap<TestCaseKey, bool>, RunnerError> {
+  let content = fs::read_to_string(file_name).map_err(|reason| RunnerError::io(file_name, reason))?;
+  let mut results = BTreeMap::new();
+  for (index, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+    let fields = parse_csv_line(line);
+    if fields.len() < 4 {
+      return Err(RunnerError::Configuration(format!("{}:{}: invalid baseline line", file_name, index + 1)));
+    }
+    let key = (fields[0].clone(), fields[1].clone(), fields[2].clone());
+    results.insert(key, fields[3] == TestResult::Success.to_string());
+  }
+  Ok(results)
+}
+
+/// Compares current test case results with baseline.
+///
+/// Test files that could not be processed are reported with an empty test case identifier,
+/// test cases from such files that passed in baseline are newly failing, not removed.
+pub fn compare(baseline: &BTreeMap<TestCaseKey, bool>, current: &BTreeMap<TestCaseKey, TestResult>) -> BaselineComparison {
+  let mut comparison = BaselineComparison::default();
+  let failed_files = current
+    .iter()
+    .filter(|((_, _, test_case_id), result)| test_case_id.is_empty() && **result == TestResult::Failure)
+    .map(|((test_directory, test_file, _), _)| (test_directory, test_file))
+    .collect::<BTreeSet<_>>();
+  let mut explained_files = BTreeSet::new();
+  for (key, passed) in baseline.iter().filter(|(key, _)| !current.contains_key(*key)) {
+    let file = (&key.0, &key.1);
+    if failed_files.contains(&file) {
+      explained_files.insert(file);
+      if *passed {
+        comparison.newly_failing.push(key.clone());
+      }
+    } else {
+      comparison.removed.push(key.clone());
+    }
+  }
+  for (key, result) in current {
+    match baseline.get(key) {
+      Some(true) if matches!(result, TestResult::Failure | TestResult::Timeout) => comparison.newly_failing.push(key.clone()),
+      Some(false) if *result == TestResult::Success => comparison.newly_passing.push(key.clone()),
+      Some(_) => {}
+      None if explained_files.contains(&(&key.0, &key.1)) => {}
+      None => comparison.added.push((key.clone(), *result)),
+    }
+  }
+  comparison.newly_failing.sort();
+  comparison
+}
+
+impl BaselineComparison {
+  /// Displays changes compared to baseline.
+  pub fn display(&self, cm: ColorMode) {
+    println!("\nChanges compared to baseline:");
+    display_section(cm, "Newly failing", self.newly_failing.iter(), |text| text.red());
+    display_section(cm, "Newly passing", self.newly_passing.iter(), |text| text.green());
+    display_section(cm, "Added", self.added.iter().map(|(key, _)| key), |text| text.blue());
+    display_section(cm, "Removed", self.removed.iter(), |text| text.yellow());
+  }
+
+  /// Writes changes compared to baseline into CSV file.
+  pub fn write(&self, file_name: &str) -> Result<(), RunnerError> {
+    let file = File::create(file_name).map_err(|reason| RunnerError::io(file_name, reason))?;
+    let mut writer = BufWriter::new(file);
+    let mut lines = vec![];
+    lines.extend(self.newly_failing.iter().map(|key| (key, "NEWLY_FAILING", TestResult::Failure.to_string())));
+    lines.extend(self.newly_passing.iter().map(|key| (key, "NEWLY_PASSING", TestResult::Success.to_string())));
+    lines.extend(self.added.iter().map(|(key, result)| (key, "ADDED", result.to_string())));
+    lines.extend(self.removed.iter().map(|key| (key, "REMOVED", String::new())));
+    for ((test_directory, test_file, test_case_id), change, result) in lines {
+      writeln!(writer, r#""{}","{}","{}","{}","{}""#, test_directory, test_file, test_case_id, change, result).map_err(|reason| RunnerError::io(file_name, reason))?;
+    }
+    writer.flush().map_err(|reason| RunnerError::io(file_name, reason))
+  }
+}
+
+/// Displays a single section of changes.
+fn display_section<'a>(cm: ColorMode, title: &str, keys: impl Iterator<Item = &'a TestCaseKey>, color: impl Fn(Text) -> Text) {
+  let keys = keys.collect::<Vec<&TestCaseKey>>();
+  color(Text::new(cm)).s(format!("  {} ({})", title, keys.len())).clear().cprintln();
+  for (test_directory, test_file, test_case_id) in keys {
+    println!("    {}/{}/{}", test_directory, test_file, test_case_id);
+  }
+}
+
+/// Splits a line of CSV file into fields, quoted fields may contain commas and escaped quotes.
+fn parse_csv_line(line: &str) -> Vec<String> {
+  let mut fields = vec![];
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut chars = line.chars().peekable();
+  while let Some(ch) = chars.next() {
+    match ch {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(std::mem::take(&mut field)),
+      _ => field.push(ch),
+    }
+  }
+  fields.push(field);
+  fields
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn key(test_file: &str, test_case_id: &str) -> TestCaseKey {
+    ("dir".to_string(), test_file.to_string(), test_case_id.to_string())
+  }
+
+  #[test]
+  fn csv_lines_are_split_into_fields() {
+    assert_eq!(vec!["a", "b", ""], parse_csv_line(r#""a","b","""#));
+    assert_eq!(vec!["a,b", r#"say "hi""#, "c"], parse_csv_line(r#""a,b","say ""hi""",c"#));
+  }
+
+  #[test]
+  fn baseline_is_loaded_from_tck_report() {
+    let file_name = std::env::temp_dir().join(format!("dsntk-baseline-{}.csv", std::process::id()));
+    fs::write(&file_name, "\"dir\",\"file\",\"001\",\"SUCCESS\",\"\"\n\n\"dir\",\"file\",\"002\",\"IGNORED\",\"\"\n").unwrap();
+    let baseline = load_baseline(file_name.to_str().unwrap()).unwrap();
+    assert_eq!(BTreeMap::from([(key("file", "001"), true), (key("file", "002"), false)]), baseline);
+    fs::write(&file_name, "\"dir\",\"file\"\n").unwrap();
+    assert!(load_baseline(file_name.to_str().unwrap()).is_err());
+    fs::remove_file(&file_name).unwrap();
+  }
+
+  #[test]
+  fn changes_are_detected() {
+    let baseline = BTreeMap::from([(key("a", "001"), true), (key("a", "002"), false), (key("a", "003"), true), (key("b", "001"), true)]);
+    let current = BTreeMap::from([
+      (key("a", "001"), TestResult::Failure),
+      (key("a", "002"), TestResult::Success),
+      (key("a", "004"), TestResult::Ignored),
+    ]);
+    let comparison = compare(&baseline, &current);
+    assert_eq!(vec![key("a", "001")], comparison.newly_failing);
+    assert_eq!(vec![key("a", "002")], comparison.newly_passing);
+    assert_eq!(vec![(key("a", "004"), TestResult::Ignored)], comparison.added);
+    assert_eq!(vec![key("a", "003"), key("b", "001")], comparison.removed);
+  }
+
+  #[test]
+  fn test_cases_of_failed_files_are_newly_failing() {
+    let baseline = BTreeMap::from([(key("a", "001"), true), (key("a", "002"), false)]);
+    let current = BTreeMap::from([(key("a", ""), TestResult::Failure)]);
+    let comparison = compare(&baseline, &current);
+    assert_eq!(vec![key("a", "001")], comparison.newly_failing);
+    assert!(comparison.added.is_empty());
+    assert!(comparison.removed.is_empty());
+  }
+}
@@ -0,0 +1,187 @@
+//! # Comparison of actual and expected values
+
+use crate::dto::{parse_decimal, SimpleDto};
+use crate::selector::Selector;
+use crate::temporal::{is_duration_type, temporal_eq};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+/// Default maximum absolute difference between compared decimals.
+pub const DEFAULT_ABSOLUTE_TOLERANCE: Decimal = dec!(0.000_000_006_7);
+
+/// Options controlling the comparison of actual and expected values.
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+  /// Flag indicating if temporal values are compared textually instead of semantically.
+  pub strict_temporal: bool,
+  /// Maximum absolute difference between compared decimals.
+  pub absolute_tolerance: Decimal,
+  /// Maximum difference between compared decimals, relative to the greater absolute value.
+  pub relative_tolerance: Decimal,
+  /// Flag indicating if lists are compared as multisets, ignoring the order of items.
+  pub unordered_lists: bool,
+}
+
+/// Comparison options overridden for tests selected by directory, file and test case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareOverride {
+  /// Selected tests.
+  #[serde(flatten)]
+  pub selector: Selector,
+  /// Maximum absolute difference between compared decimals.
+  #[serde(default)]
+  pub absolute_tolerance: Option<Decimal>,
+  /// Maximum relative difference between compared decimals.
+  #[serde(default)]
+  pub relative_tolerance: Option<Decimal>,
+  /// Flag indicating if lists are compared ignoring the order of items.
+  #[serde(default)]
+  pub unordered_lists: Option<bool>,
+}
+
+impl CompareOptions {
+  /// Returns options for the specified test, all matching overrides are applied in order.
+  pub fn for_test(&self, overrides: &[CompareOverride], directory: &str, file: &str, test_case_id: &str) -> Self {
+    let mut options = self.clone();
+    for item in overrides.iter().filter(|item| item.selector.matches(directory, file, test_case_id)) {
+      if let Some(tolerance) = item.absolute_tolerance {
+        options.absolute_tolerance = tolerance;
+      }
+      if let Some(tolerance) = item.relative_tolerance {
+        options.relative_tolerance = tolerance;
+      }
+      if let Some(unordered) = item.unordered_lists {
+        options.unordered_lists = unordered;
+      }
+    }
+    options
+  }
+}
+
+/// Result of comparing simple values.
+pub enum SimpleComparison {
+  /// Values are equal.
+  Equal,
+  /// Values differ.
+  Different,
+  /// Decimals differ more than allowed, with compared actual value, expected value and their difference.
+  OutOfTolerance(Decimal, Decimal, Decimal),
+}
+
+/// Compares simple values.
+///
+/// Decimals are equal when they differ no more than the absolute or relative tolerance.
+/// Temporal values are compared semantically, unless strict comparison is requested,
+/// any of XSD duration types may be compared with each other.
+pub fn compare_simple(actual: &SimpleDto, expected: &SimpleDto, options: &CompareOptions) -> SimpleComparison {
+  if actual == expected {
+    return SimpleComparison::Equal;
+  }
+  if let (false, false, Some(actual_type), Some(expected_type), Some(actual_text), Some(expected_text)) =
+    (actual.nil, expected.nil, &actual.typ, &expected.typ, &actual.text, &expected.text)
+  {
+    if is_decimal(actual_type) && is_decimal(expected_type) {
+      if let (Ok(a), Ok(b)) = (parse_decimal(actual_text), parse_decimal(expected_text)) {
+        // the difference too large to be represented is out of any tolerance
+        let Some(delta) = a.checked_sub(b).map(|difference| difference.abs()) else {
+          return SimpleComparison::Different;
+        };
+        // the relative limit too large to be represented exceeds any representable difference
+        let within_relative = options.relative_tolerance.checked_mul(a.abs().max(b.abs())).is_none_or(|limit| delta <= limit);
+        return if delta <= options.absolute_tolerance || within_relative {
+          SimpleComparison::Equal
+        } else {
+          SimpleComparison::OutOfTolerance(a, b, delta)
+        };
+      }
+    }
+    let same_type = actual_type == expected_type || (is_duration_type(actual_type) && is_duration_type(expected_type));
+    if !options.strict_temporal && same_type {
+      if let Some(equal) = temporal_eq(expected_type, actual_text, expected_text) {
+        return if equal { SimpleComparison::Equal } else { SimpleComparison::Different };
+      }
+    }
+  }
+  SimpleComparison::Different
+}
+
+/// Returns `true` when the type is a decimal type.
+fn is_decimal(typ: &str) -> bool {
+  matches!(typ, "xsd:decimal" | "xsd:double")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn simple(typ: &str, text: &str) -> SimpleDto {
+    SimpleDto {
+      typ: Some(typ.to_string()),
+      text: Some(text.to_string()),
+      nil: false,
+    }
+  }
+
+  fn options(strict_temporal: bool) -> CompareOptions {
+    CompareOptions {
+      strict_temporal,
+      absolute_tolerance: DEFAULT_ABSOLUTE_TOLERANCE,
+      relative_tolerance: Decimal::ZERO,
+      unordered_lists: false,
+    }
+  }
+
+  fn equal(actual: &SimpleDto, expected: &SimpleDto, options: &CompareOptions) -> bool {
+    matches!(compare_simple(actual, expected, options), SimpleComparison::Equal)
+  }
+
+  #[test]
+  fn temporal_values_are_compared_semantically() {
+    let actual = simple("xsd:dateTime", "2020-01-01T10:00:00Z");
+    let expected = simple("xsd:dateTime", "2020-01-01T10:00:00+00:00");
+    assert!(equal(&actual, &expected, &options(false)));
+    assert!(equal(&simple("xsd:dayTimeDuration", "PT24H"), &simple("xsd:duration", "P1D"), &options(false)));
+  }
+
+  #[test]
+  fn strict_mode_compares_temporal_values_textually() {
+    let actual = simple("xsd:dateTime", "2020-01-01T10:00:00Z");
+    let expected = simple("xsd:dateTime", "2020-01-01T10:00:00+00:00");
+    assert!(!equal(&actual, &expected, &options(true)));
+    assert!(equal(&actual, &actual.clone(), &options(true)));
+    assert!(!equal(&simple("xsd:dayTimeDuration", "PT24H"), &simple("xsd:duration", "P1D"), &options(true)));
+  }
+
+  #[test]
+  fn invalid_temporal_values_are_compared_textually() {
+    let actual = simple("xsd:dateTime", "2020-02-30T00:00:00");
+    let expected = simple("xsd:dateTime", "2020-03-01T00:00:00");
+    assert!(!equal(&actual, &expected, &options(false)));
+  }
+
+  #[test]
+  fn decimals_are_compared_with_tolerance() {
+    assert!(equal(&simple("xsd:decimal", "10.000000000001"), &simple("xsd:decimal", "10"), &options(false)));
+    assert!(matches!(
+      compare_simple(&simple("xsd:decimal", "10.1"), &simple("xsd:decimal", "10"), &options(false)),
+      SimpleComparison::OutOfTolerance(_, _, delta) if delta == Decimal::new(1, 1)
+    ));
+  }
+
+  #[test]
+  fn decimals_differing_beyond_range_are_different() {
+    let actual = simple("xsd:decimal", "-50000000000000000000000000000");
+    let expected = simple("xsd:decimal", "50000000000000000000000000000");
+    assert!(matches!(compare_simple(&actual, &expected, &options(false)), SimpleComparison::Different));
+    let options = CompareOptions {
+      relative_tolerance: Decimal::from(10),
+      ..options(false)
+    };
+    assert!(equal(
+      &simple("xsd:decimal", "50000000000000000000000000000"),
+      &simple("xsd:decimal", "40000000000000000000000000000"),
+      &options
+    ));
+  }
+}
@@ -0,0 +1,349 @@
+//! # Computed values stored in test files
+//!
+//! Test files may contain a `computed` value next to the `expected` value of a result node,
+//! reflecting the result recorded by an engine. Computed values may be compared with expected
+//! values, and actual values returned by the engine may be written back as computed values
+//! into copies of test files.
+
+use crate::diff::{diff, Difference};
+use crate::dto::ValueDto;
+use crate::errors::RunnerError;
+use crate::executor::TestJob;
+use crate::formatter::escape;
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const XSI: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+const NODE_COMPUTED: &str = "computed";
+const NODE_EXPECTED: &str = "expected";
+const NODE_RESULT_NODE: &str = "resultNode";
+const NODE_TEST_CASE: &str = "testCase";
+
+const ATTR_ID: &str = "id";
+
+/// Result node whose computed value differs from the expected value.
+pub struct Inconsistency {
+  /// Path of the file containing the test case.
+  pub file_path: String,
+  /// Identifier of the test.
+  pub test_id: String,
+  /// Differences between computed and expected values.
+  pub differences: Vec<Difference>,
+}
+
+/// Returns all tests whose computed value differs from the expected value,
+/// tests without computed or expected value are not checked.
+pub fn find_inconsistencies<'a>(jobs: impl Iterator<Item = &'a TestJob>) -> Vec<Inconsistency> {
+  jobs
+    .filter_map(|job| {
+      let (Some(computed), Some(expected)) = (&job.computed, &job.expected) else {
+        return None;
+      };
+      let differences = diff(computed, expected, &job.compare_options);
+      (!differences.is_empty()).then(|| Inconsistency {
+        file_path: job.file_path.clone(),
+        test_id: job.test_id.clone(),
+        differences,
+      })
+    })
+    .collect()
+}
+
+/// Writes a copy of the test file, with actual values (indexed by test identifier) stored as computed values.
+///
+/// Existing computed values are replaced, new computed values are placed before expected values.
+/// Result nodes without the actual value or without expected value are left unchanged.
+/// XML Schema instance namespace is declared in the root element, when the document does not declare it.
+pub fn write_computed(file_path: &str, output_file: &Path, actual_values: &HashMap<String, ValueDto>) -> Result<(), RunnerError> {
+  let output_file_name = output_file.display().to_string();
+  let content = fs::read_to_string(file_path).map_err(|reason| RunnerError::io(file_path, reason))?;
+  let document = roxmltree::Document::parse(&content).map_err(|reason| RunnerError::xml_document(file_path, reason))?;
+  let root_element = document.root_element();
+  let mut edits = vec![];
+  let xsi = match root_element.lookup_prefix(XSI).filter(|prefix| !prefix.is_empty()) {
+    Some(prefix) => prefix,
+    None if root_element.lookup_namespace_uri(Some("xsi")).is_some() => {
+      return Err(RunnerError::TestFile {
+        file: file_path.to_string(),
+        reason: format!("prefix 'xsi' is not bound to namespace '{}'", XSI),
+      })
+    }
+    None => {
+      let position = qualified_name_end(&content, &root_element);
+      edits.push((position..position, format!(r#" xmlns:xsi="{}""#, XSI)));
+      "xsi"
+    }
+  };
+  for test_case_node in root_element.children().filter(|n| n.tag_name().name() == NODE_TEST_CASE) {
+    let test_case_id = test_case_node.attribute(ATTR_ID).unwrap_or_default();
+    for (i, result_node) in test_case_node.children().filter(|n| n.tag_name().name() == NODE_RESULT_NODE).enumerate() {
+      let test_id = if i > 0 { format!("{}:{}", test_case_id, i) } else { test_case_id.to_string() };
+      let Some(actual) = actual_values.get(&test_id) else {
+        continue;
+      };
+      let computed_node = result_node.children().find(|n| n.tag_name().name() == NODE_COMPUTED);
+      let Some(anchor) = computed_node.or_else(|| result_node.children().find(|n| n.tag_name().name() == NODE_EXPECTED)) else {
+        continue;
+      };
+      let indent = indentation(&content, anchor.range().start);
+      let step = indentation(&content, result_node.range().start);
+      let step = match indent.strip_prefix(step) {
+        Some(step) if !step.is_empty() => step,
+        _ => "    ",
+      };
+      let writer = ValueWriter {
+        prefix: element_prefix(&content, &anchor),
+        xsi,
+        step,
+      };
+      let mut computed = format!("<{}{}>", writer.prefix, NODE_COMPUTED);
+      writer.write_value(&mut computed, actual, &format!("{}{}", indent, step));
+      computed.push_str(&format!("\n{}</{}{}>", indent, writer.prefix, NODE_COMPUTED));
+      if computed_node.is_some() {
+        edits.push((anchor.range(), computed));
+      } else {
+        let start = anchor.range().start;
+        edits.push((start..start, format!("{}\n{}", computed, indent)));
+      }
+    }
+  }
+  let mut output = content.clone();
+  for (range, text) in edits.into_iter().rev() {
+    output.replace_range(range, &text);
+  }
+  if let Some(parent) = output_file.parent() {
+    fs::create_dir_all(parent).map_err(|reason| RunnerError::io(&output_file_name, reason))?;
+  }
+  fs::write(output_file, output).map_err(|reason| RunnerError::io(&output_file_name, reason))
+}
+
+/// Writer of values in the format used in test files.
+struct ValueWriter<'a> {
+  /// Prefix of element names, including the colon, empty when elements are in default namespace.
+  prefix: &'a str,
+  /// Prefix of XML Schema instance namespace.
+  xsi: &'a str,
+  /// Single level of indentation.
+  step: &'a str,
+}
+
+impl ValueWriter<'_> {
+  /// Writes the value, each element placed in a new line with the specified indentation.
+  fn write_value(&self, out: &mut String, value: &ValueDto, indent: &str) {
+    let p = self.prefix;
+    let nested = format!("{}{}", indent, self.step);
+    if let Some(simple) = &value.simple {
+      let typ = simple.typ.as_ref().map(|typ| format!(" {}:type=\"{}\"", self.xsi, escape(typ))).unwrap_or_default();
+      if simple.nil {
+        out.push_str(&format!("\n{}<{}value{} {}:nil=\"true\"/>", indent, p, typ, self.xsi));
+      } else {
+        let text = escape(simple.text.as_deref().unwrap_or_default());
+        out.push_str(&format!("\n{}<{}value{}>{}</{}value>", indent, p, typ, text, p));
+      }
+    } else if let Some(components) = &value.components {
+      for component in components {
+        let name = component.name.as_ref().map(|name| format!(" name=\"{}\"", escape(name))).unwrap_or_default();
+        match &component.value {
+          Some(value) if !component.nil => {
+            out.push_str(&format!("\n{}<{}component{}>", indent, p, name));
+            self.write_value(out, value, &nested);
+            out.push_str(&format!("\n{}</{}component>", indent, p));
+          }
+          _ => out.push_str(&format!("\n{}<{}component{} {}:nil=\"true\"/>", indent, p, name, self.xsi)),
+        }
+      }
+    } else if let Some(list) = &value.list {
+      if list.nil {
+        out.push_str(&format!("\n{}<{}list {}:nil=\"true\"/>", indent, p, self.xsi));
+      } else {
+        out.push_str(&format!("\n{}<{}list>", indent, p));
+        for item in &list.items {
+          out.push_str(&format!("\n{}<{}item>", nested, p));
+          self.write_value(out, item, &format!("{}{}", nested, self.step));
+          out.push_str(&format!("\n{}</{}item>", nested, p));
+        }
+        out.push_str(&format!("\n{}</{}list>", indent, p));
+      }
+    } else {
+      out.push_str(&format!("\n{}<{}value {}:nil=\"true\"/>", indent, p, self.xsi));
+    }
+  }
+}
+
+/// Returns the whitespace preceding the element placed at the specified position in its line.
+fn indentation(content: &str, position: usize) -> &str {
+  let line_start = content[..position].rfind('\n').map(|index| index + 1).unwrap_or_default();
+  let indent = &content[line_start..position];
+  if indent.chars().all(char::is_whitespace) {
+    indent
+  } else {
+    ""
+  }
+}
+
+/// Returns the prefix of the element name as written in the document, including the colon.
+fn element_prefix<'a>(content: &'a str, node: &Node) -> &'a str {
+  let qualified_name = &content[node.range().start + 1..qualified_name_end(content, node)];
+  qualified_name.rfind(':').map(|index| &qualified_name[..=index]).unwrap_or_default()
+}
+
+/// Returns the position just after the element name in the start tag of the element.
+fn qualified_name_end(content: &str, node: &Node) -> usize {
+  let start = node.range().start + 1;
+  content[start..]
+    .find(|ch: char| ch.is_whitespace() || ch == '>' || ch == '/')
+    .map(|index| start + index)
+    .unwrap_or(start)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::dto::SimpleDto;
+
+  /// Writes computed values into a copy of the test file content and returns the content of the copy.
+  fn write(name: &str, content: &str, actual_values: &[(&str, &str)]) -> Result<String, RunnerError> {
+    let dir = std::env::temp_dir().join(format!("dsntk-computed-{}-{}", std::process::id(), name));
+    let (input_file, output_file) = (dir.join("input.xml"), dir.join("output.xml"));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(&input_file, content).unwrap();
+    let actual_values = actual_values
+      .iter()
+      .map(|(test_id, text)| {
+        let value = ValueDto {
+          simple: Some(SimpleDto {
+            typ: Some("xsd:string".to_string()),
+            text: Some(text.to_string()),
+            nil: false,
+          }),
+          ..Default::default()
+        };
+        (test_id.to_string(), value)
+      })
+      .collect();
+    let result = write_computed(input_file.to_str().unwrap(), &output_file, &actual_values).map(|_| fs::read_to_string(&output_file).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+    result
+  }
+
+  #[test]
+  fn computed_value_is_inserted_before_expected_value() {
+    let content = r#"<testCases xmlns="http://www.omg.org/spec/DMN/20160719/testcase" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <testCase id="001">
+    <resultNode name="a">
+      <expected>
+        <value xsi:type="xsd:string">A</value>
+      </expected>
+    </resultNode>
+    <resultNode name="b">
+      <expected>
+        <value xsi:type="xsd:string">B</value>
+      </expected>
+    </resultNode>
+  </testCase>
+</testCases>"#;
+    let expected = r#"<testCases xmlns="http://www.omg.org/spec/DMN/20160719/testcase" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <testCase id="001">
+    <resultNode name="a">
+      <computed>
+        <value xsi:type="xsd:string">X</value>
+      </computed>
+      <expected>
+        <value xsi:type="xsd:string">A</value>
+      </expected>
+    </resultNode>
+    <resultNode name="b">
+      <expected>
+        <value xsi:type="xsd:string">B</value>
+      </expected>
+    </resultNode>
+  </testCase>
+</testCases>"#;
+    assert_eq!(expected, write("insert", content, &[("001", "X")]).unwrap());
+  }
+
+  #[test]
+  fn existing_computed_value_is_replaced() {
+    let content = r#"<testCases xmlns="http://www.omg.org/spec/DMN/20160719/testcase" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <testCase id="001">
+    <resultNode name="a">
+      <expected><value xsi:type="xsd:string">A</value></expected>
+    </resultNode>
+    <resultNode name="b">
+      <computed><value xsi:type="xsd:string">old</value></computed>
+      <expected><value xsi:type="xsd:string">B</value></expected>
+    </resultNode>
+  </testCase>
+</testCases>"#;
+    let expected = r#"<testCases xmlns="http://www.omg.org/spec/DMN/20160719/testcase" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <testCase id="001">
+    <resultNode name="a">
+      <expected><value xsi:type="xsd:string">A</value></expected>
+    </resultNode>
+    <resultNode name="b">
+      <computed>
+        <value xsi:type="xsd:string">new</value>
+      </computed>
+      <expected><value xsi:type="xsd:string">B</value></expected>
+    </resultNode>
+  </testCase>
+</testCases>"#;
+    assert_eq!(expected, write("replace", content, &[("001:1", "new")]).unwrap());
+  }
+
+  #[test]
+  fn prefixes_and_indentation_of_document_are_kept() {
+    let content = "<tc:testCases xmlns:tc=\"http://www.omg.org/spec/DMN/20160719/testcase\" xmlns:i=\"http://www.w3.org/2001/XMLSchema-instance\">
+\t<tc:testCase id=\"001\">
+\t\t<tc:resultNode name=\"a\">
+\t\t\t<tc:expected>
+\t\t\t\t<tc:value i:type=\"xsd:string\">A &amp; B</tc:value>
+\t\t\t</tc:expected>
+\t\t</tc:resultNode>
+\t</tc:testCase>
+</tc:testCases>";
+    let expected = "<tc:testCases xmlns:tc=\"http://www.omg.org/spec/DMN/20160719/testcase\" xmlns:i=\"http://www.w3.org/2001/XMLSchema-instance\">
+\t<tc:testCase id=\"001\">
+\t\t<tc:resultNode name=\"a\">
+\t\t\t<tc:computed>
+\t\t\t\t<tc:value i:type=\"xsd:string\">A &lt; B</tc:value>
+\t\t\t</tc:computed>
+\t\t\t<tc:expected>
+\t\t\t\t<tc:value i:type=\"xsd:string\">A &amp; B</tc:value>
+\t\t\t</tc:expected>
+\t\t</tc:resultNode>
+\t</tc:testCase>
+</tc:testCases>";
+    assert_eq!(expected, write("prefixes", content, &[("001", "A < B")]).unwrap());
+  }
+
+  #[test]
+  fn missing_xsi_namespace_is_declared() {
+    let content = r#"<testCases xmlns="http://www.omg.org/spec/DMN/20160719/testcase">
+  <testCase id="001">
+    <resultNode name="a">
+      <expected/>
+    </resultNode>
+  </testCase>
+</testCases>"#;
+    let output = write("declare", content, &[("001", "X")]).unwrap();
+    assert!(output.starts_with(r#"<testCases xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns="#));
+    assert!(output.contains(r#"<value xsi:type="xsd:string">X</value>"#));
+    assert!(roxmltree::Document::parse(&output).is_ok());
+  }
+
+  #[test]
+  fn xsi_prefix_bound_to_other_namespace_is_rejected() {
+    let content = r#"<testCases xmlns="http://www.omg.org/spec/DMN/20160719/testcase" xmlns:xsi="urn:other">
+  <testCase id="001">
+    <resultNode name="a">
+      <expected/>
+    </resultNode>
+  </testCase>
+</testCases>"#;
+    assert!(write("reject", content, &[("001", "X")]).is_err());
+  }
+}
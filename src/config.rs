@@ -1,30 +1,448 @@
 //! # Configuration data
+//!
+//! Configuration parameters are collected from three sources,
+//! each one overriding the values provided by the previous one:
+//! - configuration file in YAML format,
+//! - environment variables prefixed with [ENV_PREFIX],
+//! - command-line flags.
 
+use crate::compare::CompareOverride;
+use crate::errors::RunnerError;
+use crate::roots::RootParams;
+use crate::server::ServerParams;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+/// Name of the configuration file used when no file is specified in command line.
+const DEFAULT_CONFIG_FILE: &str = "config.yml";
+
+/// Prefix of environment variables overriding configuration parameters.
+const ENV_PREFIX: &str = "DSNTK_RUNNER_";
+
+/// Kinds of values accepted by command-line flags.
+#[derive(Clone, Copy, PartialEq)]
+enum FlagKind {
+  /// Flag followed by a textual value.
+  Text,
+  /// Flag followed by a numeric value.
+  Number,
+  /// Boolean switch, set to `true` when the flag is present.
+  Switch,
+  /// Flag that may be repeated, each value is appended to the list.
+  /// Values in environment variables are separated with commas.
+  List,
+}
+
+/// Command-line flags overriding configuration parameters.
+/// Each entry contains the flag name, the name of the overridden parameter and the kind of the value.
+const FLAGS: &[(&str, &str, FlagKind)] = &[
+  ("--dir", "test_cases_dir_path", FlagKind::Text),
+  ("--pattern", "file_search_pattern", FlagKind::Text),
+  ("--url", "evaluate_url", FlagKind::Text),
+  ("--report", "report_file", FlagKind::Text),
+  ("--tck-report", "tck_report_file", FlagKind::Text),
+  ("--stop-on-failure", "stop_on_failure", FlagKind::Switch),
+  ("--concurrency", "concurrency", FlagKind::Number),
+  ("--junit-report", "junit_report_file", FlagKind::Text),
+  ("--json-report", "json_report_file", FlagKind::Text),
+  ("--html-report", "html_report_file", FlagKind::Text),
+  ("--baseline", "baseline_file", FlagKind::Text),
+  ("--baseline-report", "baseline_report_file", FlagKind::Text),
+  ("--expectations", "expectations_file", FlagKind::Text),
+  ("--include-test-case", "include_test_case", FlagKind::Text),
+  ("--exclude-test-case", "exclude_test_case", FlagKind::Text),
+  ("--include-invocable", "include_invocable", FlagKind::Text),
+  ("--exclude-invocable", "exclude_invocable", FlagKind::Text),
+  ("--include-label", "include_label", FlagKind::Text),
+  ("--exclude-label", "exclude_label", FlagKind::Text),
+  ("--stub", "stub", FlagKind::Switch),
+  ("--strict-temporal", "strict_temporal", FlagKind::Switch),
+  ("--absolute-tolerance", "absolute_tolerance", FlagKind::Number),
+  ("--relative-tolerance", "relative_tolerance", FlagKind::Number),
+  ("--unordered-lists", "unordered_lists", FlagKind::Switch),
+  ("--check-computed", "check_computed", FlagKind::Switch),
+  ("--write-computed", "computed_dir", FlagKind::Text),
+  ("--dry-run", "dry_run", FlagKind::Switch),
+  ("--dry-run-file", "dry_run_file", FlagKind::Text),
+  ("--wait", "server_wait_time", FlagKind::Number),
+  ("--request-timeout", "request_timeout", FlagKind::Number),
+  ("--connect-timeout", "connect_timeout", FlagKind::Number),
+  ("--retries", "retries", FlagKind::Number),
+  ("--retry-backoff", "retry_backoff", FlagKind::Number),
+  ("--record", "record_dir", FlagKind::Text),
+  ("--replay", "replay_dir", FlagKind::Text),
+  ("--include", "include_patterns", FlagKind::List),
+  ("--exclude", "exclude_patterns", FlagKind::List),
+];
 
 /// Runner configuration parameters.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigurationParams {
-  /// Path to directory containing test cases.
+  /// Path to directory containing test cases, used when no roots are specified.
+  #[serde(default)]
   pub test_cases_dir_path: String,
+  /// Root directories containing test cases, each with optional pattern and workspace prefix.
+  #[serde(default)]
+  pub roots: Vec<RootParams>,
   /// Pattern for matching test file names.
   /// Only files whose name matches the pattern will be processed.
+  #[serde(default)]
   pub file_search_pattern: String,
+  /// Glob or regex patterns matched against file paths relative to test directory,
+  /// when not empty, only files matching any of the patterns will be processed.
+  #[serde(default)]
+  pub include_patterns: Vec<String>,
+  /// Glob or regex patterns matched against file paths relative to test directory,
+  /// files matching any of the patterns will not be processed.
+  #[serde(default)]
+  pub exclude_patterns: Vec<String>,
   /// URL to service where model definitions will be evaluated.
   pub evaluate_url: String,
   /// Path to report file.
   pub report_file: String,
   /// Path to report file for TCK.
   pub tck_report_file: String,
+  /// Optional path to report file in JUnit XML format.
+  #[serde(default)]
+  pub junit_report_file: Option<String>,
+  /// Optional path to report file in JSON format, containing full details of every test.
+  #[serde(default)]
+  pub json_report_file: Option<String>,
+  /// Optional path to self-contained report file in HTML format.
+  #[serde(default)]
+  pub html_report_file: Option<String>,
+  /// Optional path to TCK report written by a previous run, used as a baseline for detecting regressions.
+  #[serde(default)]
+  pub baseline_file: Option<String>,
+  /// Optional path to report file containing changes compared to baseline.
+  #[serde(default)]
+  pub baseline_report_file: Option<String>,
+  /// Optional path to YAML file listing tests that are skipped or expected to fail.
+  #[serde(default)]
+  pub expectations_file: Option<String>,
+  /// Optional pattern, only test cases with matching identifier are executed.
+  #[serde(default)]
+  pub include_test_case: Option<String>,
+  /// Optional pattern, test cases with matching identifier are not executed.
+  #[serde(default)]
+  pub exclude_test_case: Option<String>,
+  /// Optional pattern, only tests with matching invocable or result node name are executed.
+  #[serde(default)]
+  pub include_invocable: Option<String>,
+  /// Optional pattern, tests with matching invocable or result node name are not executed.
+  #[serde(default)]
+  pub exclude_invocable: Option<String>,
+  /// Optional pattern, only test files having a matching label are executed.
+  #[serde(default)]
+  pub include_label: Option<String>,
+  /// Optional pattern, test files having a matching label are not executed.
+  #[serde(default)]
+  pub exclude_label: Option<String>,
   /// Flag indicating if testing should immediately stop on failure.
+  #[serde(default)]
   pub stop_on_failure: bool,
+  /// Flag indicating if the embedded stub engine answering with expected values
+  /// should be started at evaluation URL instead of using the real engine.
+  #[serde(default)]
+  pub stub: bool,
+  /// Flag indicating if temporal values are compared textually instead of semantically.
+  #[serde(default)]
+  pub strict_temporal: bool,
+  /// Optional maximum absolute difference between compared decimals.
+  #[serde(default)]
+  pub absolute_tolerance: Option<Decimal>,
+  /// Optional maximum difference between compared decimals, relative to the greater absolute value.
+  #[serde(default)]
+  pub relative_tolerance: Option<Decimal>,
+  /// Flag indicating if lists are compared ignoring the order of items.
+  #[serde(default)]
+  pub unordered_lists: bool,
+  /// Comparison options overridden for selected directories, files or test cases.
+  #[serde(default)]
+  pub comparison_overrides: Vec<CompareOverride>,
+  /// Flag indicating if computed values recorded in test files are compared with expected values.
+  #[serde(default)]
+  pub check_computed: bool,
+  /// Optional directory where copies of test files are written, with actual values stored as computed values.
+  #[serde(default)]
+  pub computed_dir: Option<String>,
+  /// Flag indicating if discovered tests are only listed, without contacting the engine.
+  #[serde(default)]
+  pub dry_run: bool,
+  /// Optional path to file in JSON format, listing discovered tests in dry run.
+  #[serde(default)]
+  pub dry_run_file: Option<String>,
+  /// Optional engine process started and managed by the runner.
+  #[serde(default)]
+  pub server: Option<ServerParams>,
+  /// Time in seconds to wait for the engine to respond before testing starts.
+  #[serde(default)]
+  pub server_wait_time: u64,
+  /// Optional timeout of a single evaluation request in milliseconds.
+  #[serde(default)]
+  pub request_timeout: Option<u64>,
+  /// Optional timeout of connecting to the engine in milliseconds.
+  #[serde(default)]
+  pub connect_timeout: Option<u64>,
+  /// Number of retries of requests that failed because the engine could not be reached.
+  #[serde(default)]
+  pub retries: usize,
+  /// Delay before the first retry in milliseconds, doubled before each next retry.
+  #[serde(default = "default_retry_backoff")]
+  pub retry_backoff: u64,
+  /// Optional directory where requests and raw responses of all tests are recorded.
+  #[serde(default)]
+  pub record_dir: Option<String>,
+  /// Optional directory containing recorded responses, returned instead of contacting the engine.
+  #[serde(default)]
+  pub replay_dir: Option<String>,
+  /// Number of tests evaluated in parallel, zero means the number of available CPUs.
+  #[serde(default = "default_concurrency")]
+  pub concurrency: usize,
+}
+
+/// Tests are evaluated sequentially by default.
+fn default_concurrency() -> usize {
+  1
+}
+
+/// Retries start after half a second by default.
+fn default_retry_backoff() -> u64 {
+  500
+}
+
+/// Returns configuration parameters collected from configuration file, environment and command line.
+pub fn get() -> Result<ConfigurationParams, RunnerError> {
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let mut cfg_file_name = None;
+  let mut overrides = Mapping::new();
+  let mut index = 0;
+  while index < args.len() {
+    let arg = args[index].as_str();
+    index += 1;
+    match arg {
+      "-h" | "--help" => {
+        usage();
+        std::process::exit(0);
+      }
+      "-V" | "--version" => {
+        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        std::process::exit(0);
+      }
+      "-c" | "--config" => {
+        let value = args.get(index).ok_or_else(|| missing_value(arg))?;
+        cfg_file_name = Some(value.clone());
+        index += 1;
+      }
+      _ if arg.starts_with("--") => {
+        let (flag, inline_value) = match arg.split_once('=') {
+          Some((flag, value)) => (flag, Some(value.to_string())),
+          None => (arg, None),
+        };
+        let Some((_, key, kind)) = FLAGS.iter().find(|(name, _, _)| *name == flag) else {
+          return Err(RunnerError::Configuration(format!("unknown flag '{}', run with --help to see available flags", flag)));
+        };
+        let value = match (inline_value, kind) {
+          (None, FlagKind::Switch) => Value::Bool(true),
+          (Some(value), kind) => flag_value(&value, *kind),
+          (None, kind) => {
+            let value = args.get(index).ok_or_else(|| missing_value(flag))?;
+            index += 1;
+            flag_value(value, *kind)
+          }
+        };
+        match (overrides.get_mut(*key), value) {
+          (Some(Value::Sequence(values)), Value::Sequence(value)) => values.extend(value),
+          (_, value) => {
+            overrides.insert(Value::String(key.to_string()), value);
+          }
+        }
+      }
+      _ if cfg_file_name.is_none() => cfg_file_name = Some(arg.to_string()),
+      _ => return Err(RunnerError::Configuration(format!("unexpected argument '{}', run with --help to see usage", arg))),
+    }
+  }
+  let mut params = read_config_file(cfg_file_name)?;
+  let env_params = env_overrides();
+  let overridden_dir = env_params.contains_key("test_cases_dir_path") || overrides.contains_key("test_cases_dir_path");
+  params.extend(env_params);
+  params.extend(overrides);
+  if overridden_dir {
+    // test directory specified in environment or command line replaces roots from configuration file
+    params.remove("roots");
+  }
+  let config: ConfigurationParams = serde_yaml::from_value(Value::Mapping(params)).map_err(|reason| RunnerError::Configuration(reason.to_string()))?;
+  if config.record_dir.is_some() && config.replay_dir.is_some() {
+    return Err(RunnerError::Configuration("recording and replaying responses can not be combined".to_string()));
+  }
+  Ok(config)
+}
+
+/// Returns an error reported when the value of a command-line flag is missing.
+fn missing_value(flag: &str) -> RunnerError {
+  RunnerError::Configuration(format!("missing value for flag '{}'", flag))
+}
+
+/// Reads configuration parameters from file.
+///
+/// When no file name is specified, the default configuration file is read if it exists.
+fn read_config_file(cfg_file_name: Option<String>) -> Result<Mapping, RunnerError> {
+  let cfg_file_name = match cfg_file_name {
+    Some(file_name) => file_name,
+    None if std::path::Path::new(DEFAULT_CONFIG_FILE).exists() => DEFAULT_CONFIG_FILE.to_string(),
+    None => return Ok(Mapping::new()),
+  };
+  let file_content = std::fs::read_to_string(&cfg_file_name).map_err(|reason| RunnerError::io(&cfg_file_name, reason))?;
+  match serde_yaml::from_str(&file_content) {
+    Ok(Value::Mapping(mapping)) => Ok(mapping),
+    Ok(Value::Null) => Ok(Mapping::new()),
+    Ok(_) => Err(RunnerError::Configuration(format!("configuration file '{}' does not contain a mapping", cfg_file_name))),
+    Err(reason) => Err(RunnerError::Configuration(format!("parsing configuration file '{}' failed: {}", cfg_file_name, reason))),
+  }
 }
 
-pub fn get() -> ConfigurationParams {
-  let args: Vec<String> = std::env::args().collect();
-  let cfg_file_name = if args.len() == 2 { args[1].as_str() } else { "config.yml" };
-  let err_read = format!("reading configuration file '{}' failed", cfg_file_name);
-  let file_content = std::fs::read_to_string(cfg_file_name).expect(&err_read);
-  let err_parse = format!("parsing configuration file '{}' failed", cfg_file_name);
-  serde_yaml::from_str(&file_content).expect(&err_parse)
+/// Collects configuration parameters from environment variables.
+///
+/// The name of the parameter is the lowercase name of the variable without [ENV_PREFIX],
+/// e.g. `DSNTK_RUNNER_EVALUATE_URL` overrides the `evaluate_url` parameter.
+/// Values of parameters having a textual command-line flag are never converted to numbers or booleans.
+fn env_overrides() -> Mapping {
+  std::env::vars()
+    .filter_map(|(name, value)| {
+      let key = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+      let value = match FLAGS.iter().find(|(_, param, _)| *param == key) {
+        Some((_, _, FlagKind::List)) => Value::Sequence(value.split(',').filter(|item| !item.is_empty()).map(|item| Value::String(item.to_string())).collect()),
+        Some((_, _, kind)) => flag_value(&value, *kind),
+        None => parse_scalar(&value),
+      };
+      Some((Value::String(key), value))
+    })
+    .collect()
+}
+
+/// Converts the textual value of a flag into configuration value of the specified kind.
+fn flag_value(text: &str, kind: FlagKind) -> Value {
+  match kind {
+    FlagKind::Text => Value::String(text.to_string()),
+    FlagKind::Number | FlagKind::Switch => parse_scalar(text),
+    FlagKind::List => Value::Sequence(vec![Value::String(text.to_string())]),
+  }
+}
+
+/// Parses a scalar value, falls back to string when the text is not a valid YAML scalar.
+fn parse_scalar(text: &str) -> Value {
+  match serde_yaml::from_str::<Value>(text) {
+    Ok(value @ (Value::Bool(_) | Value::Number(_))) => value,
+    _ => Value::String(text.to_string()),
+  }
+}
+
+/// Displays usage message.
+fn usage() {
+  println!(
+    r#"Usage: {name} [OPTIONS] [CONFIG_FILE]
+
+Arguments:
+  [CONFIG_FILE]               Configuration file in YAML format [default: {config}]
+
+Options:
+  -c, --config <FILE>         Configuration file in YAML format
+      --dir <PATH>            Directory containing test cases
+      --pattern <REGEX>       Pattern for matching test file names
+      --include <PATTERN>     Process only files matching the pattern, may be repeated
+      --exclude <PATTERN>     Skip files matching the pattern, may be repeated
+      --url <URL>             URL of the service evaluating models
+      --report <FILE>         Path to report file
+      --tck-report <FILE>     Path to report file for TCK
+      --junit-report <FILE>   Path to report file in JUnit XML format
+      --json-report <FILE>    Path to report file in JSON format
+      --html-report <FILE>    Path to report file in HTML format
+      --baseline <FILE>       TCK report of a previous run to compare results with,
+                              only newly failing test cases end with failure
+      --baseline-report <FILE>
+                              Path to report file containing changes compared to baseline
+      --expectations <FILE>   YAML file listing tests that are skipped or expected to fail
+      --include-test-case <REGEX>
+                              Execute only test cases with matching identifier
+      --exclude-test-case <REGEX>
+                              Skip test cases with matching identifier
+      --include-invocable <REGEX>
+                              Execute only tests with matching invocable or result node name
+      --exclude-invocable <REGEX>
+                              Skip tests with matching invocable or result node name
+      --include-label <REGEX> Execute only test files having a matching label
+      --exclude-label <REGEX> Skip test files having a matching label
+      --stop-on-failure       Stop testing after the first failure
+      --strict-temporal       Compare dates, times and durations textually instead of semantically
+      --absolute-tolerance <N>
+                              Maximum absolute difference between decimals [default: 0.0000000067]
+      --relative-tolerance <N>
+                              Maximum difference between decimals relative to the greater
+                              absolute value [default: 0]
+      --unordered-lists       Compare lists ignoring the order of items
+      --check-computed        Report test files where computed values differ from expected values
+      --write-computed <DIR>  Write copies of test files to directory, with actual values
+                              (after output extraction and cast) stored as computed values,
+                              the directory must be outside test directories
+      --dry-run               List discovered tests and invocable paths without contacting the engine
+      --dry-run-file <FILE>   Path to file in JSON format listing tests discovered in dry run
+      --stub                  Start embedded stub engine at evaluation URL,
+                              answering requests with expected values
+      --wait <SECONDS>        Time to wait for the engine to respond before testing starts [default: 0]
+      --request-timeout <MS>  Timeout of a single evaluation request in milliseconds
+      --connect-timeout <MS>  Timeout of connecting to the engine in milliseconds
+      --retries <N>           Number of retries when the engine can not be reached [default: 0]
+      --retry-backoff <MS>    Delay before the first retry, doubled before each next retry [default: 500]
+      --record <DIR>          Record requests and raw responses of all tests in directory
+      --replay <DIR>          Use responses recorded in directory instead of contacting the engine
+      --concurrency <N>       Number of tests evaluated in parallel, 0 = number of CPUs [default: 1]
+  -h, --help                  Print help
+  -V, --version               Print version
+
+Parameters are read from configuration file, then overridden by environment
+variables prefixed with {prefix} (e.g. {prefix}EVALUATE_URL), then overridden
+by command-line flags. Flag values may also be given as --flag=value, switches
+accept explicit values, e.g. --stop-on-failure=false.
+
+Required parameters, when not specified in configuration file or environment:
+--url, --report, --tck-report, and --dir unless roots are configured.
+
+Include and exclude patterns are matched against file paths relative to test
+directory. Patterns are globs (e.g. compliance-level-3/00*-test-01.xml), a glob
+matches also all files in matching directories. Patterns prefixed with 'regex:'
+are regular expressions searched in the relative path.
+
+Multiple test directories are specified in configuration file as a list of roots:
+  roots:
+    - path: ../tck/TestCases
+      name: tck                   (optional, defaults to directory name)
+      pattern: compliance-level-3 (optional, defaults to file search pattern)
+      workspace: tck              (optional prefix of workspace names)
+With more than one root, directories in reports are prefixed with root names.
+
+Tolerances and list ordering may be overridden for selected tests in configuration file,
+all matching overrides are applied in order:
+  comparison_overrides:
+    - directory: compliance-level-3/0057-feel-context (optional)
+      file: 0057-feel-context-test-01                 (optional)
+      test_case: '001'                                (optional)
+      absolute_tolerance: 0.001                       (optional)
+      relative_tolerance: 0.0001                      (optional)
+      unordered_lists: true                           (optional)
+
+The engine may be started by the runner, configured in configuration file:
+  server:
+    command: dsntk
+    args: [srv]
+    working_dir: ../dsntk           (optional)
+    env: {{ RUST_LOG: info }}         (optional)
+    readiness_url: http://127.0.0.1:22022/tck (optional, defaults to evaluation URL)
+    startup_time: 30                (optional, seconds)
+    log_dir: output                 (optional, defaults to report file directory)
+The engine is restarted when it crashes and is shut down after testing."#,
+    name = env!("CARGO_PKG_NAME"),
+    config = DEFAULT_CONFIG_FILE,
+    prefix = ENV_PREFIX
+  );
 }
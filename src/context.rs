@@ -1,8 +1,12 @@
 //! # Context for testing process
 
+use crate::diff::Difference;
+use crate::dto::ValueDto;
+use crate::errors::RunnerError;
 use crate::formatter::*;
+use crate::roots::TestRoot;
 use antex::ColorMode;
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::Path;
@@ -11,10 +15,31 @@ use std::{fmt, fs};
 use url::Url;
 
 /// Test results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TestResult {
   Success,
   Failure,
   Ignored,
+  /// Test failed as expected.
+  ExpectedFailure,
+  /// Test expected to fail has passed.
+  UnexpectedSuccess,
+  /// Engine did not respond in time.
+  Timeout,
+}
+
+impl TestResult {
+  /// Returns the severity of the result, the most severe result of all tests is the result of the test case.
+  fn severity(&self) -> u8 {
+    match self {
+      Self::Success => 0,
+      Self::UnexpectedSuccess => 1,
+      Self::Ignored => 2,
+      Self::ExpectedFailure => 3,
+      Self::Timeout => 4,
+      Self::Failure => 5,
+    }
+  }
 }
 
 impl fmt::Display for TestResult {
@@ -27,11 +52,152 @@ impl fmt::Display for TestResult {
         Self::Success => "SUCCESS",
         Self::Failure => "ERROR",
         Self::Ignored => "IGNORED",
+        Self::ExpectedFailure => "XFAIL",
+        Self::UnexpectedSuccess => "XPASS",
+        Self::Timeout => "TIMEOUT",
       }
     )
   }
 }
 
+/// Outcome of a single test.
+pub struct TestOutcome {
+  /// Result of the test.
+  pub result: TestResult,
+  /// Remarks explaining the result.
+  pub remarks: String,
+  /// Actual value returned by the engine.
+  pub actual: Option<ValueDto>,
+  /// Value expected by the test.
+  pub expected: Option<ValueDto>,
+  /// Structural differences between actual and expected values.
+  pub differences: Vec<Difference>,
+  /// Request sent to the engine and the response received.
+  pub exchange: Option<Exchange>,
+}
+
+/// Request sent to the engine and the raw response received.
+#[derive(Clone)]
+pub struct Exchange {
+  /// Name of the model containing the invocable.
+  pub model_name: String,
+  /// Path to evaluated invocable.
+  pub invocable_path: String,
+  /// Request payload in JSON format.
+  pub request: serde_json::Value,
+  /// Raw body of the response, if any was received.
+  pub response: Option<String>,
+}
+
+impl TestOutcome {
+  /// Creates an outcome of the successful test.
+  pub fn success() -> Self {
+    Self {
+      result: TestResult::Success,
+      remarks: String::new(),
+      actual: None,
+      expected: None,
+      differences: vec![],
+      exchange: None,
+    }
+  }
+
+  /// Creates an outcome of the failed test.
+  pub fn failure(remarks: impl Into<String>) -> Self {
+    Self {
+      result: TestResult::Failure,
+      remarks: remarks.into(),
+      actual: None,
+      expected: None,
+      differences: vec![],
+      exchange: None,
+    }
+  }
+
+  /// Creates an outcome of the test that has timed out.
+  pub fn timeout(remarks: impl Into<String>) -> Self {
+    Self {
+      result: TestResult::Timeout,
+      ..Self::failure(remarks)
+    }
+  }
+
+  /// Creates an outcome of the test that was not executed.
+  pub fn ignored(remarks: impl Into<String>) -> Self {
+    Self {
+      result: TestResult::Ignored,
+      ..Self::failure(remarks)
+    }
+  }
+
+  /// Converts the outcome of the test that is expected to fail,
+  /// failure becomes expected failure and success becomes unexpected success.
+  pub fn expected_to_fail(mut self, reason: &str) -> Self {
+    match self.result {
+      TestResult::Failure => {
+        self.result = TestResult::ExpectedFailure;
+        self.remarks = format!("expected failure: {} ({})", reason, self.remarks);
+      }
+      TestResult::Success => {
+        self.result = TestResult::UnexpectedSuccess;
+        self.remarks = format!("unexpectedly passing: {}", reason);
+      }
+      _ => {}
+    }
+    self
+  }
+
+  /// Sets the compared actual value.
+  pub fn with_actual(mut self, actual: ValueDto) -> Self {
+    self.actual = Some(actual);
+    self
+  }
+
+  /// Sets the value expected by the test.
+  pub fn with_expected(mut self, expected: Option<ValueDto>) -> Self {
+    self.expected = expected;
+    self
+  }
+
+  /// Sets the structural differences between actual and expected values.
+  pub fn with_differences(mut self, differences: Vec<Difference>) -> Self {
+    self.differences = differences;
+    self
+  }
+
+  /// Sets the request sent to the engine and the response received.
+  pub fn with_exchange(mut self, exchange: Exchange) -> Self {
+    self.exchange = Some(exchange);
+    self
+  }
+}
+
+/// Record of a single executed test, collected for reports.
+pub struct TestRecord {
+  /// Directory of the test file, relative to tests root directory.
+  pub directory: String,
+  /// Name of the test file without extension.
+  pub file: String,
+  /// Identifier of the test case.
+  pub test_case_id: String,
+  /// Identifier of the test.
+  pub test_id: String,
+  /// Result of the test.
+  pub result: TestResult,
+  /// Remarks explaining the result.
+  pub remarks: String,
+  /// Actual value returned by the engine.
+  pub actual: Option<ValueDto>,
+  /// Value expected by the test.
+  pub expected: Option<ValueDto>,
+  /// Structural differences between actual and expected values.
+  pub differences: Vec<Difference>,
+  /// Request sent to the engine and the response received.
+  pub exchange: Option<Exchange>,
+  /// Execution duration.
+  pub duration: Duration,
+}
+
 /// Context used during testing process.
 pub struct Context {
   /// Model RDNNs indexed by file name.
@@ -40,106 +206,188 @@ pub struct Context {
   model_names: HashMap<String, String>,
   /// Workspace names indexed by file name.
   workspace_names: HashMap<String, String>,
-  /// Test results writer.
-  report_writer: BufWriter<File>,
-  /// Test cases (TCK ready) results writer.
-  tck_report_writer: BufWriter<File>,
+  /// Names of formal parameters of business knowledge models, indexed by file name and model name.
+  bkm_parameters: HashMap<String, HashMap<String, Vec<String>>>,
+  /// Test results writer, available after opening reports.
+  report_writer: Option<BufWriter<File>>,
+  /// Test cases (TCK ready) results writer, available after opening reports.
+  tck_report_writer: Option<BufWriter<File>>,
   /// Number of tests that have passed.
   pub success_count: usize,
   /// Number of tests that have failed.
   pub failure_count: usize,
+  /// Number of tests that were not executed.
+  pub ignored_count: usize,
+  /// Number of tests that have failed as expected.
+  pub expected_failure_count: usize,
+  /// Number of tests expected to fail that have passed.
+  pub unexpected_success_count: usize,
+  /// Number of tests that have timed out.
+  pub timeout_count: usize,
   /// Total endpoint execution time in nanoseconds.
   pub execution_time: u128,
   /// Flag indicating if testing should be stopped after first test failure.
   pub stop_on_failure: bool,
-  /// Pattern for filtering files to be tested.
-  pub file_search_pattern: String,
-  /// Tests root directory.
-  pub root_dir_path: String,
+  /// Flag indicating if testing was stopped after test failure.
+  pub stopped: bool,
+  /// Root directories containing tests.
+  pub roots: Vec<TestRoot>,
   /// Test cases that have succeeded.
   pub test_case_success: BTreeSet<(String, String, String)>,
   /// Test cases that have failed.
   pub test_case_failure: BTreeMap<(String, String, String), Vec<String>>,
+  /// Test cases with results other than success or failure, like ignored or timed out test cases.
+  pub test_case_other: BTreeMap<(String, String, String), TestResult>,
   /// Number of test cases per file.
   pub test_case_count_per_file: BTreeMap<String, usize>,
   /// Execution duration per test case.
   pub test_case_duration: BTreeMap<(String, String, String), Duration>,
+  /// Records of all executed tests in execution order.
+  pub test_records: Vec<TestRecord>,
 }
 
 impl Context {
   /// Creates a new testing context.
-  pub fn new(stop_on_failure: bool, file_search_pattern: String, report_file_name: &str, tck_report_file_name: &str, root_dir: String) -> Self {
-    let report_file = File::create(report_file_name).unwrap_or_else(|e| panic!("creating output file {} failed with reason: {}", report_file_name, e));
-    let report_writer = BufWriter::new(report_file);
-    let tck_report_file = File::create(tck_report_file_name).unwrap_or_else(|e| panic!("creating output file {} failed with reason: {}", tck_report_file_name, e));
-    let tck_report_writer = BufWriter::new(tck_report_file);
+  pub fn new(stop_on_failure: bool, roots: Vec<TestRoot>) -> Self {
     Self {
       model_rdnns: HashMap::new(),
       model_names: HashMap::new(),
       workspace_names: HashMap::new(),
-      report_writer,
-      tck_report_writer,
+      bkm_parameters: HashMap::new(),
+      report_writer: None,
+      tck_report_writer: None,
       success_count: 0,
       failure_count: 0,
+      ignored_count: 0,
+      expected_failure_count: 0,
+      unexpected_success_count: 0,
+      timeout_count: 0,
       execution_time: 0,
       stop_on_failure,
-      file_search_pattern,
-      root_dir_path: root_dir + "/",
+      stopped: false,
+      roots,
       test_case_success: BTreeSet::new(),
       test_case_failure: BTreeMap::new(),
+      test_case_other: BTreeMap::new(),
       test_case_count_per_file: BTreeMap::new(),
       test_case_duration: BTreeMap::new(),
+      test_records: vec![],
     }
   }
 
-  pub fn process_model_definitions(&mut self, root_dir_path: &Path, dir_name: &str, file_name: &str) {
+  /// Creates the test results report and the test cases (TCK ready) report,
+  /// existing files are overwritten, so reports are opened only when tests are executed.
+  pub fn open_reports(&mut self, report_file_name: &str, tck_report_file_name: &str) -> Result<(), RunnerError> {
+    let report_file = File::create(report_file_name).map_err(|reason| RunnerError::io(report_file_name, reason))?;
+    self.report_writer = Some(BufWriter::new(report_file));
+    let tck_report_file = File::create(tck_report_file_name).map_err(|reason| RunnerError::io(tck_report_file_name, reason))?;
+    self.tck_report_writer = Some(BufWriter::new(tck_report_file));
+    Ok(())
+  }
+
+  pub fn process_model_definitions(&mut self, dir_name: &str, file_name: &str) -> Result<(), RunnerError> {
     let file_path = Path::new(dir_name).join(Path::new(file_name));
-    let content = fs::read_to_string(&file_path).unwrap();
-    let document = roxmltree::Document::parse(&content).unwrap();
+    let file = file_path.to_string_lossy().to_string();
+    let content = fs::read_to_string(&file_path).map_err(|reason| RunnerError::io(&file, reason))?;
+    let document = roxmltree::Document::parse(&content).map_err(|reason| RunnerError::xml_document(&file, reason))?;
     let root_node = document.root_element();
     // process model name
-    let model_name = root_node.attribute("name").unwrap();
-    self.model_names.insert(file_name.to_string(), model_name.to_string());
+    let model_name = root_node
+      .attribute("name")
+      .ok_or_else(|| RunnerError::xml(&root_node, "no mandatory attribute 'name' in model definitions").in_file(&file))?;
     // process namespace
-    let namespace = root_node.attribute("namespace").unwrap();
-    self.model_rdnns.insert(file_name.to_string(), to_rdnn(namespace));
+    let namespace = root_node
+      .attribute("namespace")
+      .ok_or_else(|| RunnerError::xml(&root_node, "no mandatory attribute 'namespace' in model definitions").in_file(&file))?;
+    let rdnn = to_rdnn(namespace).ok_or_else(|| RunnerError::xml(&root_node, format!("invalid model namespace '{}'", namespace)).in_file(&file))?;
     // process workspace names
-    self.workspace_names.insert(file_name.to_string(), workspace_name(root_dir_path, &file_path));
+    let root = self.root_of(&file)?;
+    let workspace_name = match (root.workspace.as_str(), workspace_name(&root.path, &file_path)?) {
+      ("", workspace_name) => workspace_name,
+      (prefix, workspace_name) if workspace_name.is_empty() => prefix.to_string(),
+      (prefix, workspace_name) => format!("{}/{}", prefix, workspace_name),
+    };
+    self.model_names.insert(file_name.to_string(), model_name.to_string());
+    self.model_rdnns.insert(file_name.to_string(), rdnn);
+    self.workspace_names.insert(file_name.to_string(), workspace_name);
+    // process formal parameters of business knowledge models, parameters are known only when encapsulated logic is defined
+    let bkm_parameters = root_node
+      .children()
+      .filter(|node| node.tag_name().name() == "businessKnowledgeModel")
+      .filter_map(|bkm_node| {
+        let logic_node = bkm_node.children().find(|node| node.tag_name().name() == "encapsulatedLogic")?;
+        let parameters = logic_node
+          .children()
+          .filter(|node| node.tag_name().name() == "formalParameter")
+          .filter_map(|parameter_node| parameter_node.attribute("name").map(|name| name.to_string()))
+          .collect();
+        bkm_node.attribute("name").map(|name| (name.to_string(), parameters))
+      })
+      .collect();
+    self.bkm_parameters.insert(file_name.to_string(), bkm_parameters);
+    Ok(())
   }
 
-  pub fn get_model_name(&self, file_name: &str) -> String {
-    self.model_names.get(file_name).cloned().expect("model name not found for specified file name")
+  pub fn get_model_name(&self, file_name: &str) -> Option<String> {
+    self.model_names.get(file_name).cloned()
   }
 
-  pub fn get_workspace_name(&self, file_name: &str) -> String {
-    self.workspace_names.get(file_name).cloned().expect("workspace name not found for specified file name")
+  pub fn get_workspace_name(&self, file_name: &str) -> Option<String> {
+    self.workspace_names.get(file_name).cloned()
   }
 
-  pub fn get_model_rdnn(&self, file_name: &str) -> String {
-    self.model_rdnns.get(file_name).cloned().expect("model RDNN not found for specified file name")
+  pub fn get_model_rdnn(&self, file_name: &str) -> Option<String> {
+    self.model_rdnns.get(file_name).cloned()
   }
 
-  #[allow(clippy::too_many_arguments)]
-  pub fn write_line(&mut self, test_file_name: &str, test_case_id: &str, test_id: &str, test_result: TestResult, remarks: &str, execution_duration: Duration, cm: ColorMode) {
-    let test_file_directory = dir_name_stripped_prefix(&dir_name(test_file_name), &self.root_dir_path);
-    let test_file_stem = file_stem(test_file_name);
-    let test_case_key = (test_file_directory.clone(), test_file_stem.clone(), test_case_id.to_string());
-    writeln!(
-      self.report_writer,
-      r#""{}","{}","{}","{}","{}""#,
-      test_file_directory,
-      test_file_stem,
-      test_id,
-      test_result,
-      if matches!(test_result, TestResult::Failure) { remarks } else { "" }
-    )
-    .unwrap_or_else(|e| panic!("writing line to CSV report failed with reason: {}", e));
+  /// Returns the names of formal parameters of the business knowledge model defined in the model file.
+  pub fn get_bkm_parameters(&self, file_name: &str, bkm_name: &str) -> Option<&[String]> {
+    self.bkm_parameters.get(file_name).and_then(|models| models.get(bkm_name)).map(Vec::as_slice)
+  }
+
+  pub fn write_line(
+    &mut self,
+    test_file_name: &str,
+    test_case_id: &str,
+    test_id: &str,
+    outcome: &TestOutcome,
+    execution_duration: Duration,
+    cm: ColorMode,
+  ) -> Result<(), RunnerError> {
+    let (test_result, remarks) = (outcome.result, outcome.remarks.as_str());
+    let test_case_key = self.test_case_key(test_file_name, test_case_id)?;
+    let (test_file_directory, test_file_stem) = (test_case_key.0.clone(), test_case_key.1.clone());
+    if let Some(report_writer) = &mut self.report_writer {
+      writeln!(
+        report_writer,
+        r#""{}","{}","{}","{}","{}""#,
+        test_file_directory,
+        test_file_stem,
+        test_id,
+        test_result,
+        if test_result != TestResult::Success { remarks } else { "" }
+      )
+      .map_err(|reason| RunnerError::io("CSV report", reason))?;
+    }
     self
       .test_case_count_per_file
       .entry(test_file_directory.to_string())
       .and_modify(|count| *count += 1)
       .or_insert(1);
     self.test_case_duration.insert(test_case_key.clone(), execution_duration);
+    self.test_records.push(TestRecord {
+      directory: test_file_directory.clone(),
+      file: test_file_stem.clone(),
+      test_case_id: test_case_id.to_string(),
+      test_id: test_id.to_string(),
+      result: test_result,
+      remarks: remarks.to_string(),
+      actual: outcome.actual.clone(),
+      expected: outcome.expected.clone(),
+      differences: outcome.differences.clone(),
+      exchange: outcome.exchange.clone(),
+      duration: execution_duration,
+    });
     match test_result {
       TestResult::Success => {
         self.success_count += 1;
@@ -148,6 +396,7 @@ impl Context {
       }
       TestResult::Failure => {
         self.failure_count += 1;
+        self.stopped = self.stop_on_failure;
         self
           .test_case_failure
           .entry(test_case_key)
@@ -155,99 +404,173 @@ impl Context {
           .or_insert(vec![remarks.to_string()]);
         text_failure_execution_time_remarks(cm, execution_duration.as_micros(), remarks).println();
       }
-      _ => {}
+      TestResult::Ignored => {
+        self.ignored_count += 1;
+        self.insert_other(test_case_key, test_result);
+        text_ignored_remarks(cm, remarks).println();
+      }
+      TestResult::ExpectedFailure => {
+        self.expected_failure_count += 1;
+        self.insert_other(test_case_key, test_result);
+        text_expected_failure_execution_time_remarks(cm, execution_duration.as_micros(), remarks).println();
+      }
+      TestResult::UnexpectedSuccess => {
+        self.unexpected_success_count += 1;
+        self.insert_other(test_case_key, test_result);
+        text_unexpected_success_execution_time_remarks(cm, execution_duration.as_micros(), remarks).println();
+      }
+      TestResult::Timeout => {
+        self.timeout_count += 1;
+        self.stopped = self.stop_on_failure;
+        self.insert_other(test_case_key, test_result);
+        text_timeout_execution_time_remarks(cm, execution_duration.as_micros(), remarks).println();
+      }
+    }
+    Ok(())
+  }
+
+  /// Returns the key of the test case, built from test directory relative to root directory,
+  /// test file name without extension and test case identifier.
+  /// When there is more than one root, the directory is prefixed with the name of the root.
+  pub fn test_case_key(&self, test_file_name: &str, test_case_id: &str) -> Result<(String, String, String), RunnerError> {
+    let directory = dir_name(test_file_name)?;
+    let test_file_directory = match self.root_of(test_file_name) {
+      Ok(root) => {
+        let relative = Path::new(&directory)
+          .strip_prefix(&root.path)
+          .map(|path| path.to_string_lossy().replace('\\', "/"))
+          .unwrap_or_default();
+        match (root.prefix.as_str(), relative.as_str()) {
+          ("", relative) => relative.to_string(),
+          (prefix, "") => prefix.trim_end_matches('/').to_string(),
+          (prefix, relative) => format!("{}{}", prefix, relative),
+        }
+      }
+      Err(_) => directory,
+    };
+    Ok((test_file_directory, file_stem(test_file_name)?, test_case_id.to_string()))
+  }
+
+  /// Returns the root containing the specified file.
+  pub fn root_of(&self, file_name: &str) -> Result<&TestRoot, RunnerError> {
+    self
+      .roots
+      .iter()
+      .find(|root| Path::new(file_name).starts_with(&root.path))
+      .ok_or_else(|| RunnerError::io(file_name, "file is not placed in any test directory"))
+  }
+
+  /// Saves the result of the test case other than success or failure, keeping the most severe result.
+  fn insert_other(&mut self, test_case_key: (String, String, String), test_result: TestResult) {
+    let result = self.test_case_other.entry(test_case_key).or_insert(test_result);
+    if test_result.severity() > result.severity() {
+      *result = test_result;
     }
   }
 
   pub fn display_tests_summary(&mut self, cm: ColorMode) {
     println!("\nTests:");
-    let total_count = self.success_count + self.failure_count;
-    text_summary_table(cm, total_count, self.success_count, self.failure_count).println();
-  }
-
-  pub fn display_test_cases_summary(&mut self, cm: ColorMode) {
-    let mut total = self.test_case_success.clone();
-    total.extend(self.test_case_failure.keys().cloned().collect::<HashSet<(String, String, String)>>());
-    let mut success = self.test_case_success.clone();
-    success.retain(|item| !self.test_case_failure.contains_key(item));
-    let total_count = total.len();
-    let success_count = success.len();
-    let failure_count = self.test_case_failure.len();
+    let counts = SummaryCounts {
+      success: self.success_count,
+      failure: self.failure_count,
+      ignored: self.ignored_count,
+      expected_failure: self.expected_failure_count,
+      unexpected_success: self.unexpected_success_count,
+      timeout: self.timeout_count,
+    };
+    text_summary_table(cm, &counts).println();
+  }
+
+  /// Returns the results of all test cases, a test case has failed when at least one of its tests has failed.
+  pub fn test_case_results(&self) -> BTreeMap<(String, String, String), TestResult> {
+    let mut results = self
+      .test_case_success
+      .iter()
+      .map(|key| (key.clone(), TestResult::Success))
+      .collect::<BTreeMap<(String, String, String), TestResult>>();
+    for (key, result) in &self.test_case_other {
+      let current = results.entry(key.clone()).or_insert(*result);
+      if result.severity() > current.severity() {
+        *current = *result;
+      }
+    }
+    results.extend(self.test_case_failure.keys().map(|key| (key.clone(), TestResult::Failure)));
+    results
+  }
+
+  pub fn display_test_cases_summary(&mut self, cm: ColorMode) -> Result<(), RunnerError> {
+    let results = self.test_case_results();
+    let count = |expected: TestResult| results.values().filter(|result| **result == expected).count();
+    let counts = SummaryCounts {
+      success: count(TestResult::Success),
+      failure: count(TestResult::Failure),
+      ignored: count(TestResult::Ignored),
+      expected_failure: count(TestResult::ExpectedFailure),
+      unexpected_success: count(TestResult::UnexpectedSuccess),
+      timeout: count(TestResult::Timeout),
+    };
     println!("\nTest cases:");
-    text_summary_table(cm, total_count, success_count, failure_count).println();
+    text_summary_table(cm, &counts).println();
 
     // Write the TCK compatibility report.
-    for key @ (test_directory, test_file, test_case_id) in &total {
-      if success.contains(key) {
-        writeln!(
-          self.tck_report_writer,
-          r#""{}","{}","{}","{}","""#,
-          test_directory,
-          test_file,
-          test_case_id,
-          TestResult::Success,
-        )
-        .unwrap_or_else(|reason| panic!("writing line to TCK report failed with reason: {reason}"));
-      }
-      if self.test_case_failure.contains_key(key) {
-        writeln!(
-          self.tck_report_writer,
-          r#""{}","{}","{}","{}","""#,
-          test_directory,
-          test_file,
-          test_case_id,
-          TestResult::Ignored,
-        )
-        .unwrap_or_else(|reason| panic!("writing line to TCK report failed with reason: {reason}"));
-      }
+    let Some(tck_report_writer) = &mut self.tck_report_writer else {
+      return Ok(());
+    };
+    for ((test_directory, test_file, test_case_id), result) in &results {
+      let tck_result = match result {
+        TestResult::Success | TestResult::UnexpectedSuccess => TestResult::Success,
+        _ => TestResult::Ignored,
+      };
+      writeln!(tck_report_writer, r#""{}","{}","{}","{}","""#, test_directory, test_file, test_case_id, tck_result,).map_err(|reason| RunnerError::io("TCK report", reason))?;
     }
+    tck_report_writer.flush().map_err(|reason| RunnerError::io("TCK report", reason))
   }
 }
 
 /// Retrieves the parent path without file name from given `name`.
-pub fn dir_name(name: &str) -> String {
-  Path::new(name).parent().unwrap().to_str().unwrap().to_string()
+pub fn dir_name(name: &str) -> Result<String, RunnerError> {
+  Path::new(name)
+    .parent()
+    .and_then(|parent| parent.to_str())
+    .map(|parent| parent.to_string())
+    .ok_or_else(|| RunnerError::io(name, "file has no parent directory"))
 }
 
 /// Retrieves the file name without extension.
-pub fn file_stem(name: &str) -> String {
-  Path::new(name).file_stem().unwrap().to_str().unwrap().to_string()
-}
-
-/// Removes the root directory name from the full directory path.  
-fn dir_name_stripped_prefix(full_name: &str, root_dir_name: &str) -> String {
-  let appended = root_dir_name.to_string();
-  if full_name.starts_with(&appended) {
-    full_name.strip_prefix(&appended).unwrap().to_string()
-  } else {
-    full_name.to_string()
-  }
+pub fn file_stem(name: &str) -> Result<String, RunnerError> {
+  Path::new(name)
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .map(|stem| stem.to_string())
+    .ok_or_else(|| RunnerError::io(name, "file has no name"))
 }
 
-/// Returns RDNN built from input URL.
-fn to_rdnn(input: &str) -> String {
-  let url = Url::parse(input).unwrap();
-  let segments = url.path_segments().unwrap();
+/// Returns RDNN built from input URL, or `None` when the input is not a valid URL with domain.
+fn to_rdnn(input: &str) -> Option<String> {
+  let url = Url::parse(input).ok()?;
+  let segments = url.path_segments()?;
   let mut path_segments = segments.map(|s| s.trim()).filter(|s| !s.is_empty()).collect::<Vec<&str>>();
-  let domain = url.domain().unwrap();
+  let domain = url.domain()?;
   let mut domain_segments = domain.split('.').collect::<Vec<&str>>();
   domain_segments.reverse();
   domain_segments.append(&mut path_segments);
-  domain_segments.join("/")
+  Some(domain_segments.join("/"))
 }
 
 /// Returns workspace name created from parent and child paths.
-fn workspace_name(parent_path: &Path, child_path: &Path) -> String {
-  let canonical_dir = parent_path.canonicalize().expect("failed to read directory");
-  let canonical_file_path = child_path.canonicalize().expect("failed to read file");
-  let workspace_path = canonical_file_path.parent().expect("failed to get parent directory");
+fn workspace_name(parent_path: &Path, child_path: &Path) -> Result<String, RunnerError> {
+  let parent = parent_path.to_string_lossy();
+  let child = child_path.to_string_lossy();
+  let canonical_dir = parent_path.canonicalize().map_err(|reason| RunnerError::io(&parent, reason))?;
+  let canonical_file_path = child_path.canonicalize().map_err(|reason| RunnerError::io(&child, reason))?;
+  let workspace_path = canonical_file_path.parent().ok_or_else(|| RunnerError::io(&child, "failed to get parent directory"))?;
   let workspace_name = workspace_path
     .strip_prefix(&canonical_dir)
-    .expect("failed to strip prefix in parent directory")
+    .map_err(|reason| RunnerError::io(&child, reason))?
     .to_string_lossy()
     .replace('\\', "/")
     .trim_start_matches('/')
     .trim_end_matches('/')
     .to_string();
-  workspace_name
+  Ok(workspace_name)
 }
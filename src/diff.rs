@@ -0,0 +1,298 @@
+//! # Structural differences between values
+
+use crate::compare::{compare_simple, CompareOptions, SimpleComparison};
+use crate::dto::{ComponentDto, ListDto, SimpleDto, ValueDto};
+use serde::Serialize;
+use std::fmt;
+
+/// Single difference between actual and expected values.
+#[derive(Debug, Clone, Serialize)]
+pub struct Difference {
+  /// Path to the differing part of the value, like `components[Applicant].value.list.items[2].simple.text`.
+  pub path: String,
+  /// Expected part of the value in JSON format.
+  pub expected: String,
+  /// Actual part of the value in JSON format.
+  pub actual: String,
+  /// Additional details of the difference, like the delta between decimals.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub note: Option<String>,
+}
+
+impl fmt::Display for Difference {
+  /// Converts [Difference] into string.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: expected {} got {}", self.path, self.expected, self.actual)?;
+    if let Some(note) = &self.note {
+      write!(f, " ({})", note)?;
+    }
+    Ok(())
+  }
+}
+
+/// Text reported for missing parts of the value.
+const MISSING: &str = "missing";
+
+/// Text reported as expected for actual items that have no counterpart in an unordered list.
+const UNEXPECTED: &str = "no such item";
+
+/// Returns all differences between actual and expected values, no differences means the values are equal.
+pub fn diff(actual: &ValueDto, expected: &ValueDto, options: &CompareOptions) -> Vec<Difference> {
+  let mut differences = vec![];
+  diff_value("", actual, expected, options, &mut differences);
+  differences
+}
+
+/// Collects differences between values.
+fn diff_value(path: &str, actual: &ValueDto, expected: &ValueDto, options: &CompareOptions, differences: &mut Vec<Difference>) {
+  match (actual, expected) {
+    (ValueDto { simple: Some(a), .. }, ValueDto { simple: Some(e), .. }) => diff_simple(&join(path, "simple"), a, e, options, differences),
+    (ValueDto { components: Some(a), .. }, ValueDto { components: Some(e), .. }) => diff_components(&join(path, "components"), a, e, options, differences),
+    (ValueDto { list: Some(a), .. }, ValueDto { list: Some(e), .. }) => diff_list(&join(path, "list"), a, e, options, differences),
+    (
+      ValueDto {
+        simple: None,
+        components: None,
+        list: None,
+      },
+      ValueDto {
+        simple: None,
+        components: None,
+        list: None,
+      },
+    ) => {}
+    _ => push(differences, path, json(expected), json(actual)),
+  }
+}
+
+/// Collects differences between simple values.
+fn diff_simple(path: &str, actual: &SimpleDto, expected: &SimpleDto, options: &CompareOptions, differences: &mut Vec<Difference>) {
+  match compare_simple(actual, expected, options) {
+    SimpleComparison::Equal => return,
+    SimpleComparison::OutOfTolerance(a, b, delta) => {
+      push(differences, &join(path, "text"), json(&expected.text), json(&actual.text));
+      if let Some(difference) = differences.last_mut() {
+        difference.note = Some(format!("a = {}, b = {}, delta = {}", a, b, delta));
+      }
+      return;
+    }
+    SimpleComparison::Different => {}
+  }
+  if actual.nil != expected.nil {
+    push(differences, &join(path, "isNil"), json(&expected.nil), json(&actual.nil));
+  }
+  if actual.typ != expected.typ {
+    push(differences, &join(path, "type"), json(&expected.typ), json(&actual.typ));
+  }
+  if actual.text != expected.text {
+    push(differences, &join(path, "text"), json(&expected.text), json(&actual.text));
+  }
+}
+
+/// Collects differences between components, components are matched by name.
+fn diff_components(path: &str, actual: &[ComponentDto], expected: &[ComponentDto], options: &CompareOptions, differences: &mut Vec<Difference>) {
+  let key = |index: usize, component: &ComponentDto| component.name.clone().unwrap_or_else(|| format!("#{}", index));
+  let actual = actual.iter().enumerate().map(|(index, component)| (key(index, component), component)).collect::<Vec<_>>();
+  let expected = expected.iter().enumerate().map(|(index, component)| (key(index, component), component)).collect::<Vec<_>>();
+  for (name, e) in &expected {
+    let component_path = format!("{}[{}]", path, name);
+    match actual.iter().find(|(actual_name, _)| actual_name == name) {
+      Some((_, a)) => {
+        if a.nil != e.nil {
+          push(differences, &join(&component_path, "isNil"), json(&e.nil), json(&a.nil));
+        }
+        match (&a.value, &e.value) {
+          (Some(a), Some(e)) => diff_value(&join(&component_path, "value"), a, e, options, differences),
+          (None, Some(e)) => push(differences, &join(&component_path, "value"), json(e), MISSING.to_string()),
+          (Some(a), None) => push(differences, &join(&component_path, "value"), MISSING.to_string(), json(a)),
+          (None, None) => {}
+        }
+      }
+      None => push(differences, &component_path, json(e), MISSING.to_string()),
+    }
+  }
+  for (name, a) in &actual {
+    if !expected.iter().any(|(expected_name, _)| expected_name == name) {
+      push(differences, &format!("{}[{}]", path, name), MISSING.to_string(), json(a));
+    }
+  }
+}
+
+/// Collects differences between lists, items are compared by position,
+/// or matched regardless of their order when unordered comparison is requested.
+fn diff_list(path: &str, actual: &ListDto, expected: &ListDto, options: &CompareOptions, differences: &mut Vec<Difference>) {
+  if actual.nil != expected.nil {
+    push(differences, &join(path, "isNil"), json(&expected.nil), json(&actual.nil));
+  }
+  if options.unordered_lists {
+    diff_unordered_items(path, &actual.items, &expected.items, options, differences);
+    return;
+  }
+  for index in 0..actual.items.len().max(expected.items.len()) {
+    let item_path = format!("{}.items[{}]", path, index);
+    match (actual.items.get(index), expected.items.get(index)) {
+      (Some(a), Some(e)) => diff_value(&item_path, a, e, options, differences),
+      (None, Some(e)) => push(differences, &item_path, json(e), MISSING.to_string()),
+      (Some(a), None) => push(differences, &item_path, MISSING.to_string(), json(a)),
+      (None, None) => {}
+    }
+  }
+}
+
+/// Collects differences between list items matched as multisets.
+///
+/// Each expected item is matched with the first equal actual item not matched before,
+/// expected items without a match are reported as missing, actual items without a match as unexpected.
+/// Paths of missing items contain positions in the expected list, paths of unexpected items positions in the actual list.
+fn diff_unordered_items(path: &str, actual: &[ValueDto], expected: &[ValueDto], options: &CompareOptions, differences: &mut Vec<Difference>) {
+  let mut matched = vec![false; actual.len()];
+  for (index, e) in expected.iter().enumerate() {
+    let position = actual
+      .iter()
+      .enumerate()
+      .position(|(actual_index, a)| !matched[actual_index] && diff(a, e, options).is_empty());
+    match position {
+      Some(actual_index) => matched[actual_index] = true,
+      None => push(differences, &format!("{}.items[{}]", path, index), json(e), MISSING.to_string()),
+    }
+  }
+  for (index, a) in actual.iter().enumerate().filter(|(index, _)| !matched[*index]) {
+    push(differences, &format!("{}.items[{}]", path, index), UNEXPECTED.to_string(), json(a));
+  }
+}
+
+/// Appends a difference.
+fn push(differences: &mut Vec<Difference>, path: &str, expected: String, actual: String) {
+  differences.push(Difference {
+    path: if path.is_empty() { "value".to_string() } else { path.to_string() },
+    expected,
+    actual,
+    note: None,
+  });
+}
+
+/// Appends the segment to the path.
+fn join(path: &str, segment: &str) -> String {
+  if path.is_empty() {
+    segment.to_string()
+  } else {
+    format!("{}.{}", path, segment)
+  }
+}
+
+/// Converts the value into JSON.
+fn json(value: &impl Serialize) -> String {
+  serde_json::to_string(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::compare::DEFAULT_ABSOLUTE_TOLERANCE;
+  use rust_decimal::Decimal;
+
+  fn options(unordered_lists: bool) -> CompareOptions {
+    CompareOptions {
+      strict_temporal: false,
+      absolute_tolerance: DEFAULT_ABSOLUTE_TOLERANCE,
+      relative_tolerance: Decimal::ZERO,
+      unordered_lists,
+    }
+  }
+
+  fn string(text: &str) -> ValueDto {
+    ValueDto {
+      simple: Some(SimpleDto {
+        typ: Some("xsd:string".to_string()),
+        text: Some(text.to_string()),
+        nil: false,
+      }),
+      ..Default::default()
+    }
+  }
+
+  fn list(items: &[&str]) -> ValueDto {
+    ValueDto {
+      list: Some(ListDto {
+        items: items.iter().map(|text| string(text)).collect(),
+        nil: false,
+      }),
+      ..Default::default()
+    }
+  }
+
+  fn context(components: &[(&str, ValueDto)]) -> ValueDto {
+    ValueDto {
+      components: Some(
+        components
+          .iter()
+          .map(|(name, value)| ComponentDto {
+            name: Some(name.to_string()),
+            value: Some(value.clone()),
+            nil: false,
+          })
+          .collect(),
+      ),
+      ..Default::default()
+    }
+  }
+
+  fn paths(differences: &[Difference]) -> Vec<&str> {
+    differences.iter().map(|difference| difference.path.as_str()).collect()
+  }
+
+  #[test]
+  fn equal_values_have_no_differences() {
+    let value = context(&[("Name", string("John")), ("Tags", list(&["a", "b"]))]);
+    assert!(diff(&value, &value.clone(), &options(false)).is_empty());
+  }
+
+  #[test]
+  fn differences_have_paths_to_differing_parts() {
+    let differences = diff(&string("a"), &string("b"), &options(false));
+    assert_eq!(vec!["simple.text"], paths(&differences));
+    assert_eq!(r#"simple.text: expected "b" got "a""#, differences[0].to_string());
+    let differences = diff(&string("a"), &list(&["a"]), &options(false));
+    assert_eq!(vec!["value"], paths(&differences));
+  }
+
+  #[test]
+  fn components_are_matched_by_name() {
+    let actual = context(&[("Tags", list(&["a", "c"])), ("Name", string("John")), ("Extra", string("x"))]);
+    let expected = context(&[("Name", string("John")), ("Tags", list(&["a", "b"])), ("Age", string("5"))]);
+    let differences = diff(&actual, &expected, &options(false));
+    assert_eq!(
+      vec!["components[Tags].value.list.items[1].simple.text", "components[Age]", "components[Extra]"],
+      paths(&differences)
+    );
+    assert_eq!(MISSING, differences[1].actual);
+    assert_eq!(MISSING, differences[2].expected);
+  }
+
+  #[test]
+  fn ordered_lists_are_compared_by_position() {
+    let differences = diff(&list(&["b", "a"]), &list(&["a", "b", "c"]), &options(false));
+    assert_eq!(vec!["list.items[0].simple.text", "list.items[1].simple.text", "list.items[2]"], paths(&differences));
+    assert_eq!(MISSING, differences[2].actual);
+  }
+
+  #[test]
+  fn unordered_lists_are_compared_as_multisets() {
+    assert!(diff(&list(&["b", "a", "a"]), &list(&["a", "b", "a"]), &options(true)).is_empty());
+    let differences = diff(&list(&["a", "c", "a"]), &list(&["a", "b"]), &options(true));
+    assert_eq!(vec!["list.items[1]", "list.items[1]", "list.items[2]"], paths(&differences));
+    assert_eq!(MISSING, differences[0].actual);
+    assert_eq!(UNEXPECTED, differences[1].expected);
+    assert_eq!(UNEXPECTED, differences[2].expected);
+  }
+
+  #[test]
+  fn duplicate_items_are_matched_once() {
+    let differences = diff(&list(&["a"]), &list(&["a", "a"]), &options(true));
+    assert_eq!(vec!["list.items[1]"], paths(&differences));
+    assert_eq!(MISSING, differences[0].actual);
+    let differences = diff(&list(&["a", "a"]), &list(&["a"]), &options(true));
+    assert_eq!(vec!["list.items[1]"], paths(&differences));
+    assert_eq!(UNEXPECTED, differences[0].expected);
+  }
+}
@@ -0,0 +1,110 @@
+//! # Listing of tests discovered without evaluating them
+
+use crate::errors::RunnerError;
+use crate::executor::TestJob;
+use antex::{ColorMode, StyledText, Text};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Tests that would be executed and failures detected while preparing them.
+#[derive(Default, Serialize)]
+pub struct Plan {
+  /// Tests that would be executed.
+  pub tests: Vec<PlannedTest>,
+  /// Failures detected while parsing model definitions and test files.
+  pub failures: Vec<PlanFailure>,
+}
+
+/// Single test that would be executed.
+#[derive(Serialize)]
+pub struct PlannedTest {
+  /// Path of the file containing the test case.
+  pub file: String,
+  /// Identifier of the test.
+  pub test_id: String,
+  /// Name of the model containing the invocable.
+  pub model_name: String,
+  /// RDNN namespace of the model.
+  pub model_rdnn: String,
+  /// Name of the workspace containing the model.
+  pub workspace: String,
+  /// Type of the invocable.
+  pub invocable_type: String,
+  /// Path to the invocable sent to the engine.
+  pub invocable_path: String,
+  /// Flag indicating if the test would be skipped.
+  pub skipped: bool,
+}
+
+/// Failure detected while preparing tests.
+#[derive(Serialize)]
+pub struct PlanFailure {
+  /// Path of the file that could not be processed.
+  pub file: String,
+  /// Reason of the failure.
+  pub reason: String,
+}
+
+impl Plan {
+  /// Adds the test that would be executed.
+  pub fn add_test(&mut self, job: &TestJob) {
+    self.tests.push(PlannedTest {
+      file: job.file_path.clone(),
+      test_id: job.test_id.clone(),
+      model_name: job.model_name.clone(),
+      model_rdnn: job.model_rdnn.clone(),
+      workspace: job.workspace_name.clone(),
+      invocable_type: job.params.invocable_type.clone(),
+      invocable_path: job.params.invocable_path.clone(),
+      skipped: job.is_skipped(),
+    });
+  }
+
+  /// Adds the failure detected while preparing tests.
+  pub fn add_failure(&mut self, file: &str, reason: impl ToString) {
+    self.failures.push(PlanFailure {
+      file: file.to_string(),
+      reason: reason.to_string(),
+    });
+  }
+
+  /// Displays all tests grouped by file, followed by all failures.
+  pub fn display(&self, cm: ColorMode) {
+    println!("\nDiscovered tests:");
+    let mut current_file = "";
+    for test in &self.tests {
+      if test.file != current_file {
+        current_file = &test.file;
+        println!("  {}", current_file);
+      }
+      Text::new(cm)
+        .s("    ")
+        .s(&test.test_id)
+        .s(if test.skipped { " (skipped)" } else { "" })
+        .s(format!(
+          ": model: {}, RDNN: {}, workspace: {}, type: {}, path: ",
+          test.model_name, test.model_rdnn, test.workspace, test.invocable_type
+        ))
+        .blue()
+        .s(&test.invocable_path)
+        .clear()
+        .cprintln();
+    }
+    println!("\nTotal tests: {}", self.tests.len());
+    if !self.failures.is_empty() {
+      println!("\nFailures:");
+      for failure in &self.failures {
+        Text::new(cm).s("  ").red().s(&failure.reason).clear().cprintln();
+      }
+    }
+  }
+
+  /// Writes the plan into JSON file.
+  pub fn write(&self, file_name: &str) -> Result<(), RunnerError> {
+    let file = File::create(file_name).map_err(|reason| RunnerError::io(file_name, reason))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, self).map_err(|reason| RunnerError::io(file_name, reason))?;
+    writer.flush().map_err(|reason| RunnerError::io(file_name, reason))
+  }
+}
@@ -3,10 +3,8 @@
 use crate::model::{Component, InputNode, List, Simple, Value};
 use iso8601_duration::Duration;
 use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
-use std::process::exit;
 use std::str::FromStr;
 
 /// Data transfer object for an error.
@@ -28,6 +26,13 @@ pub struct ResultDto<T> {
   pub errors: Option<Vec<ErrorDto>>,
 }
 
+impl<T> ResultDto<T> {
+  /// Returns `true` when this result contains at least one error.
+  pub fn has_errors(&self) -> bool {
+    self.errors.as_ref().is_some_and(|errors| !errors.is_empty())
+  }
+}
+
 impl<T> Display for ResultDto<T> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     let str = self
@@ -39,7 +44,7 @@ impl<T> Display for ResultDto<T> {
   }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InputNodeDto {
   #[serde(rename = "name")]
   pub name: String,
@@ -47,13 +52,21 @@ pub struct InputNodeDto {
   pub value: Option<ValueDto>,
 }
 
+/// Data transfer object for arguments of a function invocation.
+#[derive(Debug, Serialize)]
+pub enum ArgumentsDto {
+  /// Arguments passed by parameter names.
+  #[serde(rename = "named")]
+  Named(Vec<InputNodeDto>),
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OptionalValueDto {
   #[serde(rename = "value")]
   pub value: Option<ValueDto>,
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ValueDto {
   #[serde(rename = "simple", skip_serializing_if = "Option::is_none")]
   pub simple: Option<SimpleDto>,
@@ -63,7 +76,46 @@ pub struct ValueDto {
   pub list: Option<ListDto>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl ValueDto {
+  /// Returns `true` when this value represents `null`.
+  pub fn is_null(&self) -> bool {
+    match (&self.simple, &self.components, &self.list) {
+      (Some(simple), _, _) => simple.nil,
+      (_, Some(_), _) => false,
+      (_, _, Some(list)) => list.nil,
+      _ => true,
+    }
+  }
+
+  /// Casts this value to the specified type.
+  ///
+  /// Simple values are cast to XSD types, list items are cast one by one.
+  /// Casting to item definitions is left to the engine, so such values are returned unchanged.
+  pub fn cast(self, typ: &str) -> Result<ValueDto, String> {
+    if let Some(simple) = self.simple {
+      return Ok(Self {
+        simple: Some(simple.cast(typ)?),
+        ..Default::default()
+      });
+    }
+    if let Some(list) = self.list {
+      if list.nil {
+        return Ok(Self {
+          list: Some(list),
+          ..Default::default()
+        });
+      }
+      let items = list.items.into_iter().map(|item| item.cast(typ)).collect::<Result<Vec<ValueDto>, String>>()?;
+      return Ok(Self {
+        list: Some(ListDto { items, nil: false }),
+        ..Default::default()
+      });
+    }
+    Ok(self)
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SimpleDto {
   #[serde(rename = "type")]
   pub typ: Option<String>,
@@ -73,64 +125,49 @@ pub struct SimpleDto {
   pub nil: bool,
 }
 
-/// Maximum allowed difference between compared decimals.
-const EPSILON: Decimal = dec!(0.000_000_006_7);
-
-impl PartialEq for SimpleDto {
-  fn eq(&self, rhs: &Self) -> bool {
-    let mut result = self.typ == rhs.typ && self.text == rhs.text && self.nil == rhs.nil;
-    if !result {
-      // compare decimals with epsilon difference
-      if is_decimal(&self.typ) && is_decimal(&rhs.typ) && self.nil == rhs.nil {
-        let a = Decimal::from_str(&text(self)).unwrap();
-        let b = Decimal::from_str(&text(rhs)).unwrap();
-        let c = (a - b).abs();
-        result = c < EPSILON;
-        if !result {
-          println!("\n\nEncountered decimal comparison error");
-          println!(" a = {}", a);
-          println!(" b = {}", b);
-          println!(" c = {}", c);
-          println!(" ε = {}", EPSILON);
-          println!("compared decimals differ more than expected\n");
-          exit(0);
+impl SimpleDto {
+  /// Casts this simple value to the specified XSD type.
+  fn cast(self, typ: &str) -> Result<SimpleDto, String> {
+    if self.nil || !typ.starts_with("xsd:") {
+      return Ok(self);
+    }
+    let text = self.text.unwrap_or_default();
+    let text = match typ {
+      "xsd:decimal" | "xsd:double" => parse_decimal(&text)?.to_string(),
+      "xsd:integer" | "xsd:long" | "xsd:int" => {
+        let value = parse_decimal(&text)?;
+        if !value.fract().is_zero() {
+          return Err(format!("'{}' is not an integer", text));
         }
-        return result;
+        value.trunc().to_string()
       }
-      // compare durations
-      if is_duration(self) && is_duration(rhs) {
-        let a = text(self).parse::<Duration>().unwrap();
-        let b = text(rhs).parse::<Duration>().unwrap();
-        result = a == b;
-        return result;
+      "xsd:boolean" => match text.trim() {
+        "true" | "false" => text.trim().to_string(),
+        _ => return Err(format!("'{}' is not a boolean", text)),
+      },
+      "xsd:duration" => {
+        text.parse::<Duration>().map_err(|_| format!("'{}' is not a duration", text))?;
+        text
       }
-    }
-    result
+      _ => text,
+    };
+    Ok(Self {
+      typ: Some(typ.to_string()),
+      text: Some(text),
+      nil: false,
+    })
   }
 }
 
-fn text(value: &SimpleDto) -> String {
-  value.text.as_ref().unwrap().clone()
-}
-
-fn is_decimal(opt_type: &Option<String>) -> bool {
-  let Some(typ) = opt_type else {
-    return false;
-  };
-  ["xsd:decimal", "xsd:double"].contains(&typ.as_str())
-}
-
-fn is_duration(value: &SimpleDto) -> bool {
-  if value.nil {
-    return false;
-  }
-  let Some(typ) = &value.typ else {
-    return false;
-  };
-  ["xsd:duration"].contains(&typ.as_str())
+/// Parses a decimal written in plain or scientific notation.
+pub fn parse_decimal(text: &str) -> Result<Decimal, String> {
+  let text = text.trim();
+  Decimal::from_str(text)
+    .or_else(|_| Decimal::from_scientific(text))
+    .map_err(|_| format!("'{}' is not a number", text))
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ComponentDto {
   #[serde(rename = "name")]
   pub name: Option<String>,
@@ -150,7 +187,7 @@ impl From<&Component> for ComponentDto {
   }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ListDto {
   #[serde(rename = "items")]
   pub items: Vec<ValueDto>,
@@ -0,0 +1,112 @@
+//! # Errors reported by the runner
+
+use roxmltree::Node;
+use std::fmt;
+use std::process::ExitCode;
+
+/// Exit code reported when at least one test has failed.
+const EXIT_TESTS_FAILED: u8 = 1;
+
+/// Exit code reported when the configuration is invalid.
+const EXIT_CONFIGURATION_ERROR: u8 = 2;
+
+/// Exit code reported when the engine evaluating models can not be reached.
+const EXIT_ENGINE_UNREACHABLE: u8 = 3;
+
+/// Errors reported by the runner.
+#[derive(Debug)]
+pub enum RunnerError {
+  /// Invalid command-line arguments or configuration parameters.
+  Configuration(String),
+  /// Reading or writing a file failed.
+  Io { file: String, reason: String },
+  /// Parsing an XML file failed.
+  Xml { file: String, line: u32, column: u32, reason: String },
+  /// Test file is invalid.
+  TestFile { file: String, reason: String },
+  /// Model referenced in test file was not found.
+  ModelNotFound { file: String, model: String },
+  /// The engine evaluating models can not be reached.
+  EngineUnreachable { url: String, reason: String },
+  /// Some tests have failed.
+  TestsFailed { count: usize },
+  /// Some test cases that passed in baseline have failed.
+  NewFailures { count: usize },
+  /// Some model definitions or test files could not be processed while preparing tests.
+  PreparationFailed { count: usize },
+}
+
+impl RunnerError {
+  /// Creates an error reported while parsing the specified XML node.
+  ///
+  /// The file name is not known at this stage, it is set later using [RunnerError::in_file].
+  pub fn xml(node: &Node, reason: impl Into<String>) -> Self {
+    let position = node.document().text_pos_at(node.range().start);
+    Self::Xml {
+      file: String::new(),
+      line: position.row,
+      column: position.col,
+      reason: reason.into(),
+    }
+  }
+
+  /// Creates an error reported while parsing the content of the specified XML file.
+  pub fn xml_document(file: &str, error: roxmltree::Error) -> Self {
+    let position = error.pos();
+    Self::Xml {
+      file: file.to_string(),
+      line: position.row,
+      column: position.col,
+      reason: error.to_string(),
+    }
+  }
+
+  /// Creates an error reported while reading or writing the specified file.
+  pub fn io(file: &str, reason: impl fmt::Display) -> Self {
+    Self::Io {
+      file: file.to_string(),
+      reason: reason.to_string(),
+    }
+  }
+
+  /// Sets the name of the file where the XML parsing error occurred.
+  pub fn in_file(self, file: &str) -> Self {
+    match self {
+      Self::Xml { line, column, reason, .. } => Self::Xml {
+        file: file.to_string(),
+        line,
+        column,
+        reason,
+      },
+      other => other,
+    }
+  }
+
+  /// Returns the process exit code corresponding to this error.
+  pub fn exit_code(&self) -> ExitCode {
+    ExitCode::from(match self {
+      Self::Configuration(_) | Self::Io { .. } => EXIT_CONFIGURATION_ERROR,
+      Self::EngineUnreachable { .. } => EXIT_ENGINE_UNREACHABLE,
+      Self::Xml { .. } | Self::TestFile { .. } | Self::ModelNotFound { .. } | Self::TestsFailed { .. } | Self::NewFailures { .. } | Self::PreparationFailed { .. } => {
+        EXIT_TESTS_FAILED
+      }
+    })
+  }
+}
+
+impl fmt::Display for RunnerError {
+  /// Converts [RunnerError] into string.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Configuration(reason) => write!(f, "configuration error: {}", reason),
+      Self::Io { file, reason } => write!(f, "{}: {}", file, reason),
+      Self::Xml { file, line, column, reason } => write!(f, "{}:{}:{}: {}", file, line, column, reason),
+      Self::TestFile { file, reason } => write!(f, "{}: {}", file, reason),
+      Self::ModelNotFound { file, model } => write!(f, "{}: model '{}' not found", file, model),
+      Self::EngineUnreachable { url, reason } => write!(f, "engine at {} is unreachable: {}", url, reason),
+      Self::TestsFailed { count } => write!(f, "{} test(s) failed", count),
+      Self::NewFailures { count } => write!(f, "{} test case(s) newly failing compared to baseline", count),
+      Self::PreparationFailed { count } => write!(f, "{} model definition or test file(s) could not be processed", count),
+    }
+  }
+}
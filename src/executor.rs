@@ -0,0 +1,301 @@
+//! # Evaluation of tests using a pool of worker threads
+
+use crate::compare::CompareOptions;
+use crate::dto::{OptionalValueDto, ResultDto, ValueDto};
+use crate::errors::RunnerError;
+use crate::expectations::{Expectation, ExpectationKind};
+use crate::params::EvaluateParams;
+use crate::recording::read_recording;
+use reqwest::blocking::Client;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Single test prepared for evaluation, built from a result node of a test case.
+pub struct TestJob {
+  /// Path of the file containing the test case.
+  pub file_path: String,
+  /// Identifier of the test case.
+  pub test_case_id: String,
+  /// Identifier of the test, unique for each result node in test case.
+  pub test_id: String,
+  /// Name of the model containing the invocable.
+  pub model_name: String,
+  /// RDNN namespace of the model.
+  pub model_rdnn: String,
+  /// Name of the workspace containing the model.
+  pub workspace_name: String,
+  /// Name of the evaluated invocable.
+  pub invocable_name: String,
+  /// Parameters sent to the engine.
+  pub params: EvaluateParams,
+  /// Reason why the test can not be evaluated, detected while preparing the test.
+  pub error: Option<String>,
+  /// Name of the output taken from the result of a decision service returning multiple outputs.
+  pub output_name: Option<String>,
+  /// Flag indicating if the evaluation is expected to end with an error.
+  pub error_result: bool,
+  /// Optional type the actual value should be cast to before comparison.
+  pub cast: Option<String>,
+  /// Expected value.
+  pub expected: Option<ValueDto>,
+  /// Value computed by an engine, recorded in the test file.
+  pub computed: Option<ValueDto>,
+  /// Options of comparing actual and expected values.
+  pub compare_options: CompareOptions,
+  /// Expectation for the known failing test, if any.
+  pub expectation: Option<Expectation>,
+  /// Name of the file containing recorded request and response, relative to recordings directory.
+  pub recording_name: String,
+}
+
+impl TestJob {
+  /// Returns `true` when the test should not be evaluated.
+  pub fn is_skipped(&self) -> bool {
+    self.expectation.as_ref().is_some_and(|expectation| expectation.status == ExpectationKind::Skip)
+  }
+}
+
+/// Response received from the engine.
+pub enum Response {
+  /// Result returned by the engine.
+  Received(ResultDto<OptionalValueDto>),
+  /// Request failed or the response could not be decoded.
+  Failed(String),
+  /// The engine did not respond in time.
+  TimedOut(String),
+  /// The engine could not be reached.
+  Unreachable(RunnerError),
+}
+
+/// Result of evaluating a single test.
+pub struct Evaluation {
+  /// Response received from the engine.
+  pub response: Response,
+  /// Raw body of the response, if any was received.
+  pub body: Option<String>,
+  /// Duration of the request.
+  pub duration: Duration,
+}
+
+/// Policy of retrying requests that failed because the engine could not be reached.
+#[derive(Clone, Copy)]
+pub struct RetryPolicy {
+  /// Maximum number of retries.
+  pub retries: usize,
+  /// Delay before the first retry, doubled before each next retry.
+  pub backoff: Duration,
+}
+
+/// Creates HTTP client with optional request and connect timeouts.
+pub fn build_client(request_timeout: Option<Duration>, connect_timeout: Option<Duration>) -> Result<Client, RunnerError> {
+  let mut builder = Client::builder();
+  if let Some(timeout) = request_timeout {
+    builder = builder.timeout(timeout);
+  }
+  if let Some(timeout) = connect_timeout {
+    builder = builder.connect_timeout(timeout);
+  }
+  builder
+    .build()
+    .map_err(|reason| RunnerError::Configuration(format!("creating HTTP client failed: {}", reason)))
+}
+
+/// Sends the test parameters to the engine and returns the received response.
+///
+/// Requests failing because the engine could not be reached are retried according to the retry policy.
+pub fn evaluate(client: &Client, evaluate_url: &str, job: &TestJob, policy: RetryPolicy) -> Evaluation {
+  let execution_start_time = Instant::now();
+  let mut body = None;
+  let mut backoff = policy.backoff;
+  let mut attempt = 0;
+  let response = loop {
+    match client.post(evaluate_url).json(&job.params).send().and_then(|response| response.text()) {
+      Ok(text) => {
+        let response = decode(&text);
+        body = Some(text);
+        break response;
+      }
+      // connection failures, including connect timeouts, are retried before request timeouts are checked
+      Err(reason) if reason.is_connect() && attempt < policy.retries => {
+        attempt += 1;
+        thread::sleep(backoff);
+        backoff *= 2;
+      }
+      Err(reason) if reason.is_connect() => {
+        break Response::Unreachable(RunnerError::EngineUnreachable {
+          url: evaluate_url.to_string(),
+          reason: reason.to_string(),
+        })
+      }
+      Err(reason) if reason.is_timeout() => break Response::TimedOut(format!("request timed out: {}", reason)),
+      Err(reason) => break Response::Failed(reason.to_string()),
+    }
+  };
+  Evaluation {
+    response,
+    body,
+    duration: execution_start_time.elapsed(),
+  }
+}
+
+/// Returns the response recorded for the test, without contacting the engine.
+///
+/// The recorded response is used only when the recorded request equals the request prepared for the test.
+pub fn replay(replay_dir: &str, job: &TestJob) -> Evaluation {
+  let execution_start_time = Instant::now();
+  let request = serde_json::to_value(&job.params).unwrap_or_default();
+  let (response, body) = match read_recording(replay_dir, &job.recording_name) {
+    Ok(recording) if recording.request != request => (Response::Failed(format!("recorded request differs from the current request: {}", recording.request)), None),
+    Ok(recording) => match recording.response {
+      Some(body) => (decode(&body), Some(body)),
+      None => (Response::Failed(recording.error.unwrap_or_else(|| "no response recorded".to_string())), None),
+    },
+    Err(reason) => (Response::Failed(reason), None),
+  };
+  Evaluation {
+    response,
+    body,
+    duration: execution_start_time.elapsed(),
+  }
+}
+
+/// Decodes the raw body of the response received from the engine.
+fn decode(body: &str) -> Response {
+  match serde_json::from_str::<ResultDto<OptionalValueDto>>(body) {
+    Ok(result) => Response::Received(result),
+    Err(reason) => Response::Failed(format!("error decoding response body: {}", reason)),
+  }
+}
+
+/// Evaluates items using the specified number of worker threads
+/// and consumes the results strictly in the order of items, regardless of completion order.
+///
+/// The `consume` function returns `false` when processing should stop,
+/// items not yet evaluated are then skipped.
+pub fn run_ordered<T, R, E, C>(items: &[T], concurrency: usize, evaluate: E, mut consume: C) -> Result<(), RunnerError>
+where
+  T: Sync,
+  R: Send,
+  E: Fn(&T) -> R + Sync,
+  C: FnMut(&T, R) -> Result<bool, RunnerError>,
+{
+  let next_index = AtomicUsize::new(0);
+  let stopped = AtomicBool::new(false);
+  thread::scope(|scope| {
+    let (sender, receiver) = mpsc::channel::<(usize, R)>();
+    for _ in 0..concurrency.max(1) {
+      let sender = sender.clone();
+      let (next_index, stopped, evaluate) = (&next_index, &stopped, &evaluate);
+      scope.spawn(move || loop {
+        let index = next_index.fetch_add(1, Ordering::SeqCst);
+        if index >= items.len() || stopped.load(Ordering::SeqCst) {
+          break;
+        }
+        if sender.send((index, evaluate(&items[index]))).is_err() {
+          break;
+        }
+      });
+    }
+    drop(sender);
+    let mut pending = BTreeMap::new();
+    let mut expected_index = 0;
+    for (index, result) in receiver.iter() {
+      pending.insert(index, result);
+      while let Some(result) = pending.remove(&expected_index) {
+        let proceed = consume(&items[expected_index], result);
+        expected_index += 1;
+        if !matches!(proceed, Ok(true)) {
+          stopped.store(true, Ordering::SeqCst);
+          return proceed.map(|_| ());
+        }
+      }
+    }
+    Ok(())
+  })
+}
+
+/// Returns the number of worker threads, zero means the number of available CPUs.
+pub fn worker_count(concurrency: usize) -> usize {
+  if concurrency > 0 {
+    concurrency
+  } else {
+    thread::available_parallelism().map(|count| count.get()).unwrap_or(1)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::Mutex;
+
+  #[test]
+  fn results_are_consumed_in_the_order_of_items() {
+    // earlier items take longer, so they complete after later items
+    let items = (0..8_u64).collect::<Vec<u64>>();
+    let completed = Mutex::new(vec![]);
+    let mut consumed = vec![];
+    run_ordered(
+      &items,
+      4,
+      |item| {
+        thread::sleep(Duration::from_millis((8 - item) * 10));
+        completed.lock().unwrap().push(*item);
+        item * 2
+      },
+      |item, result| {
+        consumed.push((*item, result));
+        Ok(true)
+      },
+    )
+    .unwrap();
+    assert_eq!((0..8).map(|item| (item, item * 2)).collect::<Vec<(u64, u64)>>(), consumed);
+    assert_ne!(items, *completed.lock().unwrap());
+  }
+
+  #[test]
+  fn processing_stops_when_requested() {
+    let items = (0..100_usize).collect::<Vec<usize>>();
+    let evaluated = AtomicUsize::new(0);
+    let mut consumed = vec![];
+    run_ordered(
+      &items,
+      2,
+      |item| {
+        evaluated.fetch_add(1, Ordering::SeqCst);
+        thread::sleep(Duration::from_millis(1));
+        *item
+      },
+      |item, _| {
+        consumed.push(*item);
+        Ok(*item < 3)
+      },
+    )
+    .unwrap();
+    assert_eq!(vec![0, 1, 2, 3], consumed);
+    assert!(evaluated.load(Ordering::SeqCst) < items.len());
+  }
+
+  #[test]
+  fn consume_error_stops_processing() {
+    let items = (0..10_usize).collect::<Vec<usize>>();
+    let mut consumed = vec![];
+    let result = run_ordered(
+      &items,
+      3,
+      |item| *item,
+      |item, _| {
+        consumed.push(*item);
+        if *item == 2 {
+          Err(RunnerError::Configuration("stop".to_string()))
+        } else {
+          Ok(true)
+        }
+      },
+    );
+    assert!(result.is_err());
+    assert_eq!(vec![0, 1, 2], consumed);
+  }
+}
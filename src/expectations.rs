@@ -0,0 +1,51 @@
+//! # Expected results of known failing tests
+
+use crate::errors::RunnerError;
+use crate::selector::Selector;
+use serde::Deserialize;
+use std::fs;
+
+/// Kinds of expectations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpectationKind {
+  /// Test is not executed and is reported as ignored.
+  Skip,
+  /// Test is executed and is expected to fail.
+  Xfail,
+}
+
+/// Expected result of tests selected by directory, file and test case.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Expectation {
+  /// Selected tests.
+  #[serde(flatten)]
+  pub selector: Selector,
+  /// Kind of the expectation.
+  pub status: ExpectationKind,
+  /// Reason why the test is skipped or expected to fail.
+  #[serde(default)]
+  pub reason: String,
+}
+
+/// Loads expectations from YAML file.
+///
+/// The file contains a list of entries like:
+/// ```yaml
+/// - directory: compliance-level-3/0089-nested-inputdata-imports
+///   file: 0089-nested-inputdata-imports-test-01
+///   test_case: "001"
+///   status: xfail
+///   reason: nested imports are not supported yet
+/// ```
+pub fn load_expectations(file_name: &str) -> Result<Vec<Expectation>, RunnerError> {
+  let content = fs::read_to_string(file_name).map_err(|reason| RunnerError::io(file_name, reason))?;
+  serde_yaml::from_str::<Option<Vec<Expectation>>>(&content)
+    .map(Option::unwrap_or_default)
+    .map_err(|reason| RunnerError::Configuration(format!("parsing expectations file '{}' failed: {}", file_name, reason)))
+}
+
+/// Returns the first expectation selecting the specified test.
+pub fn find_expectation<'a>(expectations: &'a [Expectation], directory: &str, file: &str, test_case_id: &str) -> Option<&'a Expectation> {
+  expectations.iter().find(|expectation| expectation.selector.matches(directory, file, test_case_id))
+}
@@ -0,0 +1,219 @@
+//! # Selecting test files by include and exclude patterns
+
+use crate::errors::RunnerError;
+use regex::Regex;
+use std::path::Path;
+
+/// Prefix of patterns that are regular expressions, other patterns are globs.
+const REGEX_PREFIX: &str = "regex:";
+
+/// Pattern matched against file paths relative to test directory.
+enum FilePattern {
+  /// Glob, matches the whole path or any of its parent directories.
+  Glob(Regex),
+  /// Regular expression searched in the path.
+  Regex(Regex),
+}
+
+impl FilePattern {
+  /// Compiles the pattern, patterns prefixed with [REGEX_PREFIX] are regular expressions.
+  fn new(pattern: &str) -> Result<Self, RunnerError> {
+    let invalid = |reason: String| RunnerError::Configuration(format!("invalid file pattern '{}': {}", pattern, reason));
+    match pattern.strip_prefix(REGEX_PREFIX) {
+      Some(regex) => Regex::new(regex).map(Self::Regex).map_err(|reason| invalid(reason.to_string())),
+      None => {
+        let regex = glob_to_regex(pattern.trim_matches('/')).map_err(invalid)?;
+        Regex::new(&regex).map(Self::Glob).map_err(|reason| invalid(reason.to_string()))
+      }
+    }
+  }
+
+  /// Returns `true` when the relative path matches the pattern.
+  fn is_match(&self, relative_path: &str) -> bool {
+    match self {
+      Self::Glob(regex) => regex.is_match(relative_path) || relative_path.match_indices('/').any(|(index, _)| regex.is_match(&relative_path[..index])),
+      Self::Regex(regex) => regex.is_match(relative_path),
+    }
+  }
+}
+
+/// Filter selecting test files by the pattern matched against full file path
+/// and by lists of include and exclude patterns matched against paths relative to test directory.
+pub struct FileFilter {
+  /// Pattern matched against the full file path.
+  search_pattern: Regex,
+  /// When not empty, only files matching any of these patterns are selected.
+  include: Vec<FilePattern>,
+  /// Files matching any of these patterns are not selected.
+  exclude: Vec<FilePattern>,
+}
+
+impl FileFilter {
+  /// Creates a file filter.
+  pub fn new(search_pattern: &str, include: &[String], exclude: &[String]) -> Result<Self, RunnerError> {
+    Ok(Self {
+      search_pattern: Regex::new(search_pattern).map_err(|reason| RunnerError::Configuration(format!("invalid file search pattern: {}", reason)))?,
+      include: include.iter().map(|pattern| FilePattern::new(pattern)).collect::<Result<_, _>>()?,
+      exclude: exclude.iter().map(|pattern| FilePattern::new(pattern)).collect::<Result<_, _>>()?,
+    })
+  }
+
+  /// Returns `true` when include or exclude patterns are specified.
+  pub fn has_patterns(&self) -> bool {
+    !self.include.is_empty() || !self.exclude.is_empty()
+  }
+
+  /// Returns `true` when the model file should be processed.
+  ///
+  /// Include and exclude patterns select test files only, models are selected by the search pattern.
+  pub fn is_model_match(&self, full_path: &Path) -> bool {
+    self.search_pattern.is_match(&full_path.display().to_string())
+  }
+
+  /// Returns `true` when the test file should be processed.
+  pub fn is_match(&self, root_dir: &Path, full_path: &Path) -> bool {
+    let full_name = full_path.display().to_string();
+    let relative_path = full_path.strip_prefix(root_dir).unwrap_or(full_path).display().to_string();
+    self.search_pattern.is_match(&full_name)
+      && (self.include.is_empty() || self.include.iter().any(|pattern| pattern.is_match(&relative_path)))
+      && !self.exclude.iter().any(|pattern| pattern.is_match(&relative_path))
+  }
+}
+
+/// Converts glob into anchored regular expression.
+///
+/// Supported are `*` (any characters except `/`), `**` (any characters), `?`,
+/// character classes like `[a-z]` or `[!0-9]` and alternatives like `{dmn,xml}`.
+/// Unclosed character classes and alternatives are reported as errors.
+fn glob_to_regex(glob: &str) -> Result<String, String> {
+  let mut regex = String::from("^");
+  let mut chars = glob.chars().peekable();
+  let mut alternatives = 0;
+  while let Some(ch) = chars.next() {
+    match ch {
+      '*' if chars.peek() == Some(&'*') => {
+        chars.next();
+        if chars.peek() == Some(&'/') {
+          chars.next();
+          regex.push_str("(?:.*/)?");
+        } else {
+          regex.push_str(".*");
+        }
+      }
+      '*' => regex.push_str("[^/]*"),
+      '?' => regex.push_str("[^/]"),
+      '[' => {
+        regex.push('[');
+        if chars.peek() == Some(&'!') {
+          chars.next();
+          regex.push('^');
+        }
+        let mut closed = false;
+        for ch in chars.by_ref() {
+          if ch == ']' {
+            closed = true;
+            break;
+          }
+          if ch == '\\' || ch == '[' {
+            regex.push('\\');
+          }
+          regex.push(ch);
+        }
+        if !closed {
+          return Err("unclosed character class '['".to_string());
+        }
+        regex.push(']');
+      }
+      '{' => {
+        alternatives += 1;
+        regex.push_str("(?:");
+      }
+      ',' if alternatives > 0 => regex.push('|'),
+      '}' if alternatives > 0 => {
+        alternatives -= 1;
+        regex.push(')');
+      }
+      _ => regex.push_str(&regex::escape(&ch.to_string())),
+    }
+  }
+  if alternatives > 0 {
+    return Err("unclosed alternatives '{'".to_string());
+  }
+  regex.push('$');
+  Ok(regex)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn matches(glob: &str, path: &str) -> bool {
+    FilePattern::new(glob).unwrap().is_match(path)
+  }
+
+  #[test]
+  fn single_star_does_not_cross_directories() {
+    assert!(matches("*.xml", "a-test-01.xml"));
+    assert!(!matches("*.xml", "dir/a-test-01.xml"));
+    assert!(matches("dir/*-01.xml", "dir/a-test-01.xml"));
+  }
+
+  #[test]
+  fn double_star_matches_any_directories() {
+    assert!(matches("**/*.xml", "a.xml"));
+    assert!(matches("**/*.xml", "x/y/a.xml"));
+    assert!(matches("level-3/**/a.xml", "level-3/a.xml"));
+    assert!(matches("level-3/**/a.xml", "level-3/x/y/a.xml"));
+    assert!(matches("level-3/**", "level-3/x/a.xml"));
+  }
+
+  #[test]
+  fn question_mark_matches_single_character() {
+    assert!(matches("00?1.xml", "0001.xml"));
+    assert!(!matches("00?1.xml", "001.xml"));
+    assert!(!matches("a?b", "a/b"));
+  }
+
+  #[test]
+  fn character_classes() {
+    assert!(matches("00[0-2]1.xml", "0021.xml"));
+    assert!(!matches("00[0-2]1.xml", "0031.xml"));
+    assert!(matches("00[!0-2]1.xml", "0031.xml"));
+    assert!(!matches("00[!0-2]1.xml", "0011.xml"));
+  }
+
+  #[test]
+  fn alternatives() {
+    assert!(matches("a.{dmn,xml}", "a.dmn"));
+    assert!(matches("a.{dmn,xml}", "a.xml"));
+    assert!(!matches("a.{dmn,xml}", "a.txt"));
+    assert!(matches("a,b.xml", "a,b.xml"));
+  }
+
+  #[test]
+  fn special_characters_are_escaped() {
+    assert!(matches("a+b.xml", "a+b.xml"));
+    assert!(!matches("a.xml", "axml"));
+  }
+
+  #[test]
+  fn glob_matching_directory_matches_files_inside() {
+    assert!(matches("level-3/0089-slow", "level-3/0089-slow/0089-slow-test-01.xml"));
+    assert!(matches("level-3/0089-*", "level-3/0089-slow/nested/test.xml"));
+    assert!(matches("/level-3/", "level-3/0001/test.xml"));
+    assert!(!matches("level-3/0089", "level-3/0089-slow/test.xml"));
+  }
+
+  #[test]
+  fn regex_patterns_are_searched() {
+    assert!(matches("regex:00(89|90)", "level-3/0090-x/test.xml"));
+    assert!(!matches("regex:^00", "level-3/0090-x/test.xml"));
+  }
+
+  #[test]
+  fn unclosed_constructs_are_reported() {
+    assert_eq!(glob_to_regex("a{b,c"), Err("unclosed alternatives '{'".to_string()));
+    assert_eq!(glob_to_regex("a[bc"), Err("unclosed character class '['".to_string()));
+    assert!(FilePattern::new("a{b").is_err_and(|e| e.to_string().contains("unclosed alternatives")));
+  }
+}
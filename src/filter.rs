@@ -0,0 +1,63 @@
+//! # Filtering tests by test case identifier, invocable name and labels
+
+use crate::config::ConfigurationParams;
+use crate::errors::RunnerError;
+use regex::Regex;
+
+/// Pair of optional include and exclude patterns.
+#[derive(Default)]
+struct Patterns {
+  /// When specified, at least one of the values must match this pattern.
+  include: Option<Regex>,
+  /// When specified, none of the values may match this pattern.
+  exclude: Option<Regex>,
+}
+
+impl Patterns {
+  /// Compiles include and exclude patterns, the name is used in error messages.
+  fn new(name: &str, include: Option<&str>, exclude: Option<&str>) -> Result<Self, RunnerError> {
+    let compile = |kind: &str, pattern: Option<&str>| {
+      pattern
+        .map(|pattern| Regex::new(pattern).map_err(|reason| RunnerError::Configuration(format!("invalid {} {} pattern: {}", kind, name, reason))))
+        .transpose()
+    };
+    Ok(Self {
+      include: compile("include", include)?,
+      exclude: compile("exclude", exclude)?,
+    })
+  }
+
+  /// Returns `true` when the specified values satisfy both patterns.
+  fn accepts<'a>(&self, values: impl Iterator<Item = &'a str> + Clone) -> bool {
+    self.include.as_ref().is_none_or(|include| values.clone().any(|value| include.is_match(value)))
+      && self.exclude.as_ref().is_none_or(|exclude| !values.clone().any(|value| exclude.is_match(value)))
+  }
+}
+
+/// Filter selecting tests to be executed.
+pub struct TestFilter {
+  /// Patterns matched against test case identifiers.
+  test_case: Patterns,
+  /// Patterns matched against invocable and result node names.
+  invocable: Patterns,
+  /// Patterns matched against labels of the test file.
+  label: Patterns,
+}
+
+impl TestFilter {
+  /// Creates a filter from configuration parameters.
+  pub fn new(config: &ConfigurationParams) -> Result<Self, RunnerError> {
+    Ok(Self {
+      test_case: Patterns::new("test case", config.include_test_case.as_deref(), config.exclude_test_case.as_deref())?,
+      invocable: Patterns::new("invocable", config.include_invocable.as_deref(), config.exclude_invocable.as_deref())?,
+      label: Patterns::new("label", config.include_label.as_deref(), config.exclude_label.as_deref())?,
+    })
+  }
+
+  /// Returns `true` when the test should be executed.
+  pub fn accepts(&self, test_case_id: &str, invocable_name: &str, result_node_name: &str, labels: &[String]) -> bool {
+    self.test_case.accepts([test_case_id].into_iter())
+      && self.invocable.accepts([invocable_name, result_node_name].into_iter())
+      && self.label.accepts(labels.iter().map(String::as_str))
+  }
+}
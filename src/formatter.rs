@@ -57,11 +57,46 @@ pub fn text_failure_execution_time_remarks(cm: ColorMode, time: u128, remarks: &
   Text::new(cm).red().s("failure").clear().space().s(time).space().s("µs").space().yellow().s(remarks).clear()
 }
 
-pub fn text_summary_table(cm: ColorMode, total_count: usize, success_count: usize, failure_count: usize) -> Text {
-  let (success_percentage, failure_percentage) = perc(total_count, success_count, failure_count);
-  let color_success = if success_count > 0 { Color::Green } else { Color::White };
-  let color_failure = if failure_count > 0 { Color::Red } else { Color::White };
+pub fn text_ignored_remarks(cm: ColorMode, remarks: &str) -> Text {
+  Text::new(cm).yellow().s("ignored").clear().space().s(remarks)
+}
+
+pub fn text_expected_failure_execution_time_remarks(cm: ColorMode, time: u128, remarks: &str) -> Text {
+  Text::new(cm).yellow().s("xfail").clear().space().s(time).space().s("µs").space().s(remarks)
+}
+
+pub fn text_unexpected_success_execution_time_remarks(cm: ColorMode, time: u128, remarks: &str) -> Text {
   Text::new(cm)
+    .magenta()
+    .s("xpass")
+    .clear()
+    .space()
+    .s(time)
+    .space()
+    .s("µs")
+    .space()
+    .yellow()
+    .s(remarks)
+    .clear()
+}
+
+pub fn text_timeout_execution_time_remarks(cm: ColorMode, time: u128, remarks: &str) -> Text {
+  Text::new(cm).red().s("timeout").clear().space().s(time).space().s("µs").space().yellow().s(remarks).clear()
+}
+
+/// Numbers of tests (or test cases) with specific results, displayed in summary table.
+pub struct SummaryCounts {
+  pub success: usize,
+  pub failure: usize,
+  pub ignored: usize,
+  pub expected_failure: usize,
+  pub unexpected_success: usize,
+  pub timeout: usize,
+}
+
+pub fn text_summary_table(cm: ColorMode, counts: &SummaryCounts) -> Text {
+  let total_count = counts.success + counts.failure + counts.ignored + counts.expected_failure + counts.unexpected_success + counts.timeout;
+  let mut text = Text::new(cm)
     .s("┌─────────┬───────┬─────────┐")
     .nl()
     .s("│")
@@ -79,45 +114,55 @@ pub fn text_summary_table(cm: ColorMode, total_count: usize, success_count: usiz
     .s("│")
     .nl()
     .s("├─────────┼───────┼─────────┤")
-    .nl()
-    .s("│ ")
-    .color(color_success)
-    .s("Success")
-    .clear()
-    .s(" │ ")
-    .color(color_success)
-    .s(format!("{:>5}", success_count))
-    .clear()
-    .s(" │")
-    .color(color_success)
-    .s(format!("{:>7.2}", success_percentage))
-    .perc()
-    .clear()
-    .s(" │")
-    .nl()
+    .nl();
+  text = summary_row(text, "Success", counts.success, total_count, Color::Green);
+  text = summary_row(text, "Failure", counts.failure, total_count, Color::Red);
+  if counts.timeout > 0 {
+    text = summary_row(text, "Timeout", counts.timeout, total_count, Color::Red);
+  }
+  if counts.ignored > 0 {
+    text = summary_row(text, "Ignored", counts.ignored, total_count, Color::Yellow);
+  }
+  if counts.expected_failure > 0 {
+    text = summary_row(text, "XFail", counts.expected_failure, total_count, Color::Yellow);
+  }
+  if counts.unexpected_success > 0 {
+    text = summary_row(text, "XPass", counts.unexpected_success, total_count, Color::Magenta);
+  }
+  text.s("└─────────┴───────┴─────────┘")
+}
+
+/// Appends a single row of the summary table, the row is colored only when the count is not zero.
+fn summary_row(text: Text, label: &str, count: usize, total: usize, color: Color) -> Text {
+  let color = if count > 0 { color } else { Color::White };
+  text
     .s("│ ")
-    .color(color_failure)
-    .s("Failure")
+    .color(color)
+    .s(format!("{:<7}", label))
     .clear()
     .s(" │ ")
-    .color(color_failure)
-    .s(format!("{:>5}", failure_count))
+    .color(color)
+    .s(format!("{:>5}", count))
     .clear()
     .s(" │")
-    .color(color_failure)
-    .s(format!("{:>7.2}", failure_percentage))
+    .color(color)
+    .s(format!("{:>7.2}", perc(total, count)))
     .perc()
     .clear()
     .s(" │")
     .nl()
-    .s("└─────────┴───────┴─────────┘")
 }
 
-/// Calculates percentages.
-fn perc(total: usize, success: usize, failure: usize) -> (f64, f64) {
+/// Calculates percentage.
+pub fn perc(total: usize, count: usize) -> f64 {
   if total > 0 {
-    ((success * 100) as f64 / total as f64, (failure * 100) as f64 / total as f64)
+    (count * 100) as f64 / total as f64
   } else {
-    (0.0, 0.0)
+    0.0
   }
 }
+
+/// Escapes special XML and HTML characters in text content and attribute values.
+pub fn escape(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,165 @@
+//! # Self-contained test report in HTML format
+
+use crate::context::{TestRecord, TestResult};
+use crate::errors::RunnerError;
+use crate::formatter::{escape, perc};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+
+/// Results reported in summary tables, in display order.
+const RESULTS: [TestResult; 6] = [
+  TestResult::Success,
+  TestResult::Failure,
+  TestResult::Timeout,
+  TestResult::Ignored,
+  TestResult::ExpectedFailure,
+  TestResult::UnexpectedSuccess,
+];
+
+/// Styles embedded in the report.
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1, h2 { font-weight: normal; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 4px 10px; text-align: left; }
+td.number { text-align: right; }
+.SUCCESS { color: #1a7f37; } .ERROR, .TIMEOUT { color: #cf222e; } .IGNORED, .XFAIL { color: #9a6700; } .XPASS { color: #8250df; }
+.bar { background: #eee; width: 200px; height: 10px; } .bar div { background: #2da44e; height: 10px; }
+details { border: 1px solid #ccc; margin: 4px 0; padding: 4px 8px; }
+summary { cursor: pointer; }
+pre { background: #f6f8fa; padding: 6px; overflow-x: auto; margin: 4px 0; }
+table.diff td { vertical-align: top; font-family: monospace; white-space: pre; border: none; padding: 0 8px; }
+table.diff tr.changed td.actual { background: #ffebe9; } table.diff tr.changed td.expected { background: #dafbe1; }
+"#;
+
+/// Writes HTML report with summary tables, pass rates per directory and details of every failure.
+pub fn write_html_report(file_name: &str, records: &[TestRecord], test_case_results: &BTreeMap<(String, String, String), TestResult>) -> Result<(), RunnerError> {
+  let mut html = String::new();
+  let _ = write!(
+    html,
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>DMN TCK report</title>\n<style>{}</style>\n</head>\n<body>\n<h1>DMN TCK report</h1>\n",
+    STYLE
+  );
+  html.push_str("<h2>Tests</h2>\n");
+  write_summary(&mut html, records.iter().map(|record| record.result));
+  html.push_str("<h2>Test cases</h2>\n");
+  write_summary(&mut html, test_case_results.values().copied());
+  html.push_str("<h2>Directories</h2>\n");
+  write_directories(&mut html, records);
+  html.push_str("<h2>Failures</h2>\n");
+  write_failures(&mut html, records);
+  html.push_str("</body>\n</html>\n");
+  fs::write(file_name, html).map_err(|reason| RunnerError::io(file_name, reason))
+}
+
+/// Writes the table with the number and percentage of each result.
+fn write_summary(html: &mut String, results: impl Iterator<Item = TestResult>) {
+  let mut counts = BTreeMap::new();
+  let mut total = 0;
+  for result in results {
+    *counts.entry(result.to_string()).or_insert(0_usize) += 1;
+    total += 1;
+  }
+  html.push_str("<table>\n<tr><th>Result</th><th>Count</th><th>Percentage</th></tr>\n");
+  let _ = writeln!(html, "<tr><th>TOTAL</th><td class=\"number\">{}</td><td></td></tr>", total);
+  for result in RESULTS {
+    let name = result.to_string();
+    let count = counts.get(&name).copied().unwrap_or_default();
+    if count > 0 || matches!(result, TestResult::Success | TestResult::Failure) {
+      let _ = writeln!(
+        html,
+        "<tr><td class=\"{0}\">{0}</td><td class=\"number\">{1}</td><td class=\"number\">{2:.2}%</td></tr>",
+        name,
+        count,
+        perc(total, count)
+      );
+    }
+  }
+  html.push_str("</table>\n");
+}
+
+/// Writes the table with pass rates per directory.
+fn write_directories(html: &mut String, records: &[TestRecord]) {
+  let mut directories: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+  for record in records {
+    let (total, success) = directories.entry(&record.directory).or_default();
+    *total += 1;
+    if matches!(record.result, TestResult::Success | TestResult::UnexpectedSuccess) {
+      *success += 1;
+    }
+  }
+  html.push_str("<table>\n<tr><th>Directory</th><th>Tests</th><th>Passed</th><th colspan=\"2\">Pass rate</th></tr>\n");
+  for (directory, (total, success)) in directories {
+    let rate = perc(total, success);
+    let _ = writeln!(
+      html,
+      "<tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{:.2}%</td><td><div class=\"bar\"><div style=\"width: {:.0}%\"></div></div></td></tr>",
+      escape(directory),
+      total,
+      success,
+      rate,
+      rate
+    );
+  }
+  html.push_str("</table>\n");
+}
+
+/// Writes collapsible details of every failed test.
+fn write_failures(html: &mut String, records: &[TestRecord]) {
+  let failures = records
+    .iter()
+    .filter(|record| matches!(record.result, TestResult::Failure | TestResult::Timeout))
+    .collect::<Vec<&TestRecord>>();
+  if failures.is_empty() {
+    html.push_str("<p>No failures.</p>\n");
+    return;
+  }
+  for record in failures {
+    let _ = writeln!(
+      html,
+      "<details>\n<summary><span class=\"{0}\">{0}</span> {1}/{2}/{3}: {4}</summary>",
+      record.result,
+      escape(&record.directory),
+      escape(&record.file),
+      escape(&record.test_id),
+      escape(&record.remarks)
+    );
+    if let Some(exchange) = &record.exchange {
+      let _ = writeln!(html, "<p>Invocable: <code>{}</code></p>", escape(&exchange.invocable_path));
+    }
+    write_differences(html, record);
+    if let Some(exchange) = &record.exchange {
+      let request = serde_json::to_string_pretty(&exchange.request).unwrap_or_default();
+      let _ = writeln!(html, "<details><summary>Request</summary><pre>{}</pre></details>", escape(&request));
+      if let Some(response) = &exchange.response {
+        let _ = writeln!(html, "<details><summary>Response</summary><pre>{}</pre></details>", escape(response));
+      }
+    }
+    html.push_str("</details>\n");
+  }
+}
+
+/// Writes the list of structural differences, followed by collapsible actual and expected values.
+fn write_differences(html: &mut String, record: &TestRecord) {
+  if !record.differences.is_empty() {
+    html.push_str("<table class=\"diff\">\n<tr><th>path</th><th>expected</th><th>actual</th><th>note</th></tr>\n");
+    for difference in &record.differences {
+      let _ = writeln!(
+        html,
+        "<tr class=\"changed\"><td>{}</td><td class=\"expected\">{}</td><td class=\"actual\">{}</td><td>{}</td></tr>",
+        escape(&difference.path),
+        escape(&difference.expected),
+        escape(&difference.actual),
+        escape(difference.note.as_deref().unwrap_or_default())
+      );
+    }
+    html.push_str("</table>\n");
+  }
+  for (title, value) in [("Actual", &record.actual), ("Expected", &record.expected)] {
+    if let Some(value) = value {
+      let value = serde_json::to_string_pretty(value).unwrap_or_default();
+      let _ = writeln!(html, "<details><summary>{}</summary><pre>{}</pre></details>", title, escape(&value));
+    }
+  }
+}
@@ -0,0 +1,93 @@
+//! # Test report in JSON format
+
+use crate::context::{TestRecord, TestResult};
+use crate::diff::Difference;
+use crate::dto::ValueDto;
+use crate::errors::RunnerError;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Report of the whole test run.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunReport<'a> {
+  /// Number of executed tests.
+  total: usize,
+  /// Number of tests that have passed.
+  success: usize,
+  /// Number of tests that have failed.
+  failure: usize,
+  /// Number of tests that were not executed.
+  ignored: usize,
+  /// Number of tests that have failed as expected.
+  expected_failure: usize,
+  /// Number of tests expected to fail that have passed.
+  unexpected_success: usize,
+  /// Number of tests that have timed out.
+  timeout: usize,
+  /// Details of all executed tests.
+  tests: Vec<TestReport<'a>>,
+}
+
+/// Report of a single test.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestReport<'a> {
+  directory: &'a str,
+  file: &'a str,
+  test_case_id: &'a str,
+  test_id: &'a str,
+  model_name: Option<&'a str>,
+  invocable_path: Option<&'a str>,
+  request: Option<&'a serde_json::Value>,
+  response: Option<&'a str>,
+  actual: Option<&'a ValueDto>,
+  expected: Option<&'a ValueDto>,
+  differences: &'a [Difference],
+  result: String,
+  remarks: &'a str,
+  /// Execution duration in microseconds.
+  duration: u128,
+}
+
+impl<'a> From<&'a TestRecord> for TestReport<'a> {
+  fn from(record: &'a TestRecord) -> Self {
+    let exchange = record.exchange.as_ref();
+    Self {
+      directory: &record.directory,
+      file: &record.file,
+      test_case_id: &record.test_case_id,
+      test_id: &record.test_id,
+      model_name: exchange.map(|exchange| exchange.model_name.as_str()),
+      invocable_path: exchange.map(|exchange| exchange.invocable_path.as_str()),
+      request: exchange.map(|exchange| &exchange.request),
+      response: exchange.and_then(|exchange| exchange.response.as_deref()),
+      actual: record.actual.as_ref(),
+      expected: record.expected.as_ref(),
+      differences: &record.differences,
+      result: record.result.to_string(),
+      remarks: &record.remarks,
+      duration: record.duration.as_micros(),
+    }
+  }
+}
+
+/// Writes JSON report containing full details of every executed test.
+pub fn write_json_report(file_name: &str, records: &[TestRecord]) -> Result<(), RunnerError> {
+  let count = |result: TestResult| records.iter().filter(|record| record.result == result).count();
+  let report = RunReport {
+    total: records.len(),
+    success: count(TestResult::Success),
+    failure: count(TestResult::Failure),
+    ignored: count(TestResult::Ignored),
+    expected_failure: count(TestResult::ExpectedFailure),
+    unexpected_success: count(TestResult::UnexpectedSuccess),
+    timeout: count(TestResult::Timeout),
+    tests: records.iter().map(TestReport::from).collect(),
+  };
+  let file = File::create(file_name).map_err(|reason| RunnerError::io(file_name, reason))?;
+  let mut writer = BufWriter::new(file);
+  serde_json::to_writer_pretty(&mut writer, &report).map_err(|reason| RunnerError::io(file_name, reason))?;
+  writer.flush().map_err(|reason| RunnerError::io(file_name, reason))
+}
@@ -0,0 +1,112 @@
+//! # Test report in JUnit XML format
+
+use crate::context::{TestRecord, TestResult};
+use crate::errors::RunnerError;
+use crate::formatter::escape;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+
+/// Writes JUnit XML report with one test suite per test file and one test case per result node.
+pub fn write_junit_report(file_name: &str, records: &[TestRecord]) -> Result<(), RunnerError> {
+  let file = File::create(file_name).map_err(|reason| RunnerError::io(file_name, reason))?;
+  let mut writer = BufWriter::new(file);
+  write_suites(&mut writer, records).map_err(|reason| RunnerError::io(file_name, reason))
+}
+
+/// Writes all test suites, records are grouped by test file.
+fn write_suites(w: &mut impl Write, records: &[TestRecord]) -> std::io::Result<()> {
+  let mut suites: BTreeMap<(&str, &str), Vec<&TestRecord>> = BTreeMap::new();
+  for record in records {
+    suites.entry((&record.directory, &record.file)).or_default().push(record);
+  }
+  let (tests, failures, skipped, time) = totals(records.iter());
+  writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(
+    w,
+    r#"<testsuites name="DMN TCK" tests="{}" failures="{}" skipped="{}" time="{}">"#,
+    tests,
+    failures,
+    skipped,
+    seconds(time)
+  )?;
+  for ((directory, file), records) in suites {
+    let (tests, failures, skipped, time) = totals(records.iter().copied());
+    writeln!(
+      w,
+      r#"  <testsuite name="{}/{}" tests="{}" failures="{}" skipped="{}" time="{}">"#,
+      escape(directory),
+      escape(file),
+      tests,
+      failures,
+      skipped,
+      seconds(time)
+    )?;
+    for record in records {
+      let name = if record.test_id.is_empty() { &record.file } else { &record.test_id };
+      write!(
+        w,
+        r#"    <testcase classname="{}.{}" name="{}" time="{}""#,
+        escape(directory),
+        escape(file),
+        escape(name),
+        seconds(record.duration)
+      )?;
+      match record.result {
+        TestResult::Success | TestResult::UnexpectedSuccess => writeln!(w, "/>")?,
+        TestResult::Ignored | TestResult::ExpectedFailure => {
+          writeln!(w, ">")?;
+          writeln!(w, r#"      <skipped message="{}"/>"#, escape(&record.remarks))?;
+          writeln!(w, "    </testcase>")?;
+        }
+        TestResult::Failure | TestResult::Timeout => {
+          writeln!(w, ">")?;
+          writeln!(
+            w,
+            r#"      <failure message="{}" type="{}">{}</failure>"#,
+            escape(&record.remarks),
+            if record.result == TestResult::Timeout { "timeout" } else { "failure" },
+            escape(&failure_details(record))
+          )?;
+          writeln!(w, "    </testcase>")?;
+        }
+      }
+    }
+    writeln!(w, "  </testsuite>")?;
+  }
+  writeln!(w, "</testsuites>")?;
+  w.flush()
+}
+
+/// Returns the number of tests, failures, skipped tests and the total duration.
+fn totals<'a>(records: impl Iterator<Item = &'a TestRecord>) -> (usize, usize, usize, Duration) {
+  records.fold((0, 0, 0, Duration::ZERO), |(tests, failures, skipped, time), record| {
+    (
+      tests + 1,
+      failures + usize::from(matches!(record.result, TestResult::Failure | TestResult::Timeout)),
+      skipped + usize::from(matches!(record.result, TestResult::Ignored | TestResult::ExpectedFailure)),
+      time + record.duration,
+    )
+  })
+}
+
+/// Returns the failure remarks followed by actual and expected values in JSON format.
+fn failure_details(record: &TestRecord) -> String {
+  let mut details = record.remarks.clone();
+  for difference in &record.differences {
+    details.push_str(&format!("\n  {}", difference));
+  }
+  if let Some(actual) = &record.actual {
+    details.push_str(&format!("\n  actual: {}", serde_json::to_string(actual).unwrap_or_default()));
+  }
+  if let Some(expected) = &record.expected {
+    details.push_str(&format!("\nexpected: {}", serde_json::to_string(expected).unwrap_or_default()));
+  }
+  details
+}
+
+/// Formats the duration in seconds.
+fn seconds(duration: Duration) -> String {
+  format!("{:.6}", duration.as_secs_f64())
+}
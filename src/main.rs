@@ -1,117 +1,327 @@
 //! # Test runner for DMN™ Technology Compatibility Kit
 
-use crate::context::{Context, TestResult};
-use crate::dto::{InputNodeDto, OptionalValueDto, ResultDto, ValueDto};
+use crate::compare::{CompareOptions, CompareOverride, DEFAULT_ABSOLUTE_TOLERANCE};
+use crate::computed::Inconsistency;
+use crate::context::{Context, Exchange, TestOutcome, TestResult};
+use crate::diff::{diff, Difference};
+use crate::dry_run::Plan;
+use crate::dto::{ArgumentsDto, InputNodeDto, OptionalValueDto, ResultDto, ValueDto};
+use crate::errors::RunnerError;
+use crate::executor::{build_client, worker_count, Evaluation, Response, RetryPolicy, TestJob};
+use crate::expectations::{find_expectation, Expectation, ExpectationKind};
+use crate::filter::TestFilter;
 use crate::formatter::{text_executing_test_case, text_green_ok, text_parsing_test_file};
-use crate::model::{parse_test_file, Value};
+use crate::model::{parse_test_file, TestCaseType};
 use crate::params::EvaluateParams;
-use antex::{Color, ColorMode, StyledText, Text};
-use regex::Regex;
+use crate::recording::{recording_name, write_recording, Recording};
+use crate::roots::{build_roots, TestRoot};
+use crate::server::ManagedServer;
+use antex::{ColorMode, StyledText, Text};
 use reqwest::blocking::Client;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
+use std::process::ExitCode;
 use std::time::{Duration, Instant};
 
+mod baseline;
+mod compare;
+mod computed;
 mod config;
 mod context;
+mod diff;
+mod dry_run;
 mod dto;
+mod errors;
+mod executor;
+mod expectations;
+mod file_filter;
+mod filter;
 mod formatter;
+mod html_report;
+mod json_report;
+mod junit;
 mod model;
 mod params;
+mod readiness;
+mod recording;
+mod roots;
+mod selector;
+mod server;
+mod stub;
+mod temporal;
 
-const DEFAULT_REMARK: &str = "";
 const DIFFERS_REMARK: &str = "actual result differs from expected";
+const ERROR_EXPECTED_REMARK: &str = "expected error result, but actual result is a value";
+const ERROR_EXPECTED_NULL_REMARK: &str = "expected error result, but actual result is null without error details";
 
 /// Main entrypoint of the runner.
-fn main() {
+fn main() -> ExitCode {
+  match run() {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(reason) => {
+      eprintln!("{}", reason);
+      reason.exit_code()
+    }
+  }
+}
+
+/// Runs all tests selected by configuration.
+fn run() -> Result<(), RunnerError> {
   let cm = ColorMode::default();
-  // read configuration from file
-  let config = config::get();
-  // prepare the full directory path where test are stored
-  let root_dir = Path::new(&config.test_cases_dir_path).canonicalize().expect("reading test directory failed");
+  // read configuration from file, environment and command line
+  let config = config::get()?;
+  // prepare the full directory paths where test are stored
+  let roots = build_roots(&config)?;
+  // the baseline may be overwritten by the TCK report of this run, so it is loaded before tests start
+  let baseline = config.baseline_file.as_deref().map(baseline::load_baseline).transpose()?;
+  let settings = TestSettings {
+    filter: TestFilter::new(&config)?,
+    expectations: config.expectations_file.as_deref().map(expectations::load_expectations).transpose()?.unwrap_or_default(),
+    compare_options: CompareOptions {
+      strict_temporal: config.strict_temporal,
+      absolute_tolerance: config.absolute_tolerance.unwrap_or(DEFAULT_ABSOLUTE_TOLERANCE),
+      relative_tolerance: config.relative_tolerance.unwrap_or_default(),
+      unordered_lists: config.unordered_lists,
+    },
+    compare_overrides: config.comparison_overrides.clone(),
+  };
   // create the testing context
-  let mut ctx = Context::new(
-    config.stop_on_failure,
-    config.file_search_pattern,
-    &config.report_file,
-    &config.tck_report_file,
-    root_dir.to_string_lossy().to_string(),
-  );
-  if root_dir.exists() && root_dir.is_dir() {
-    print!("Starting DMN TCK runner...");
-    let client = Client::new();
-    println!("ok");
-    println!("File search pattern: {}", ctx.file_search_pattern);
-    print!("Searching DMN files in directory: {} ... ", root_dir.display());
+  let mut ctx = Context::new(config.stop_on_failure, roots);
+  print!("Starting DMN TCK runner...");
+  let client = build_client(config.request_timeout.map(Duration::from_millis), config.connect_timeout.map(Duration::from_millis))?;
+  let retry_policy = RetryPolicy {
+    retries: config.retries,
+    backoff: Duration::from_millis(config.retry_backoff),
+  };
+  let worker_count = worker_count(config.concurrency);
+  println!("ok");
+  if !config.include_patterns.is_empty() {
+    println!("Include patterns: {}", config.include_patterns.join(", "));
+  }
+  if !config.exclude_patterns.is_empty() {
+    println!("Exclude patterns: {}", config.exclude_patterns.join(", "));
+  }
+  let mut root_files = vec![];
+  for root in &ctx.roots {
+    if ctx.roots.len() > 1 {
+      println!("Test root: {}", root.name);
+    }
+    println!("File search pattern: {}", root.file_search_pattern);
+    print!("Searching DMN files in directory: {} ... ", root.path.display());
     let mut files = BTreeMap::new();
-    let pattern = Regex::new(&ctx.file_search_pattern).expect("parsing search pattern failed");
-    search_files(&root_dir, &pattern, &mut files);
+    search_files(root, &root.path, &mut files)?;
+    if root.file_filter.has_patterns() {
+      // models are processed only in directories containing selected test files
+      files.retain(|_, (_, files_xml)| !files_xml.is_empty());
+    }
     println!("ok");
-    for (dir_name, (files_dmn, files_xml)) in files {
-      // retrieve model names and namespaces from DMN files
-      for file_dmn in files_dmn {
-        ctx.process_model_definitions(&root_dir, &dir_name, &file_dmn);
-      }
-      // execute all tests
-      for file_xml in files_xml {
-        let file_path = format!("{}/{}", dir_name, file_xml);
-        execute_tests(&mut ctx, &file_path, &client, &config.evaluate_url, cm);
+    root_files.extend(files);
+  }
+  // prepare all tests, retrieve model names and namespaces from DMN files first
+  let mut steps = vec![];
+  for (dir_name, (mut files_dmn, mut files_xml)) in root_files {
+    files_dmn.sort();
+    files_xml.sort();
+    for file_dmn in files_dmn {
+      if let Err(reason) = ctx.process_model_definitions(&dir_name, &file_dmn) {
+        steps.push(Step::Failure(format!("{}/{}", dir_name, file_dmn), reason));
       }
     }
-    //------------------------------------------------------------------------------------------------------------------
-    // Report number of tests per file.
-    //------------------------------------------------------------------------------------------------------------------
-    println!("\nTests per file:");
-    let mut total_per_file = 0;
-    println!("┌────────────────────────────────────────────────────────────────────────┬────────┐");
-    for (name, count) in &ctx.test_case_count_per_file {
-      println!("│ {:70} │ {:6} │", name, count);
-      total_per_file += count;
+    for file_xml in files_xml {
+      prepare_tests(&ctx, format!("{}/{}", dir_name, file_xml), &settings, &mut steps);
     }
-    println!("├────────────────────────────────────────────────────────────────────────┼────────┤");
-    println!("│                                                                  Total │ {:6} │", total_per_file);
-    println!("└────────────────────────────────────────────────────────────────────────┴────────┘");
-    //------------------------------------------------------------------------------------------------------------------
-    // Report execution durations.
-    //------------------------------------------------------------------------------------------------------------------
-    let durations = ctx
-      .test_case_duration
-      .iter()
-      .map(|(key, duration)| (*duration, key.clone()))
-      .collect::<BTreeMap<Duration, (String, String, String)>>();
-    for (d, k) in durations {
-      println!("{:12} µs  {}/{}/{}", d.as_micros(), k.0, k.1, k.2);
+  }
+  // display test files where computed values differ from expected values, only parsed test files are needed
+  if config.check_computed {
+    let jobs = steps.iter().filter_map(|step| match step {
+      Step::Test(job) => Some(job.as_ref()),
+      _ => None,
+    });
+    display_inconsistencies(&computed::find_inconsistencies(jobs), cm);
+  }
+  // list discovered tests without contacting the engine
+  if config.dry_run {
+    return dry_run(&steps, config.dry_run_file.as_deref(), cm);
+  }
+  // start the stub engine answering with expected values
+  if config.stub {
+    print!("Starting stub engine at {} ... ", config.evaluate_url);
+    let jobs = steps.iter().filter_map(|step| match step {
+      Step::Test(job) => Some(job.as_ref()),
+      _ => None,
+    });
+    stub::start(&config.evaluate_url, stub::expected_responses(jobs))?;
+    println!("ok");
+  }
+  // start the engine managed by the runner, the engine is shut down when the runner exits
+  let server = match &config.server {
+    Some(server_params) => {
+      print!("Starting engine '{}' ... ", server_params.command);
+      let server = ManagedServer::start(server_params, &config.evaluate_url, &config.report_file)?;
+      println!("ok");
+      Some(server)
     }
-    // Display summary of successful/failed tests
-    ctx.display_tests_summary(cm);
-    // display summary of successful/failed test cases
-    ctx.display_test_cases_summary(cm);
-    // display timings summary
-    let total_count = ctx.success_count + ctx.failure_count;
-    let requests_per_second = total_count as f64 / (ctx.execution_time as f64 / 1_000_000_000.0);
-    println!("\nTimings:");
-    println!("┌───────────────────────────┬────────┐");
-    println!("│ Average request time [ms] │ {:>6.03} │", (ctx.execution_time as f64) / (total_count as f64) / 1_000_000.0);
-    println!("│       Requests per second │ {:>6.0} │", requests_per_second);
-    println!("└───────────────────────────┴────────┘");
-  } else {
-    usage();
+    None => None,
+  };
+  // check that the engine is ready, unless recorded responses are used
+  if config.replay_dir.is_none() {
+    print!("Waiting for engine at {} ... ", config.evaluate_url);
+    readiness::wait_for_server(&config.evaluate_url, Duration::from_secs(config.server_wait_time))?;
+    println!("ok");
+  }
+  // reports are created only when tests are executed, so previous reports are left intact
+  // by the dry run and when the engine could not be started or reached
+  ctx.open_reports(&config.report_file, &config.tck_report_file)?;
+  // execute all tests, results are reported in the order of steps
+  let run_start_time = Instant::now();
+  executor::run_ordered(
+    &steps,
+    worker_count,
+    |step| match step {
+      Step::Test(job) if !job.is_skipped() && job.error.is_none() => Some(match &config.replay_dir {
+        Some(replay_dir) => executor::replay(replay_dir, job),
+        None => evaluate(&client, &config.evaluate_url, job, retry_policy, server.as_ref()),
+      }),
+      _ => None,
+    },
+    |step, evaluation| {
+      report_step(&mut ctx, step, evaluation, config.record_dir.as_deref(), cm)?;
+      Ok(!ctx.stopped)
+    },
+  )?;
+  let run_duration = run_start_time.elapsed();
+  //------------------------------------------------------------------------------------------------------------------
+  // Report number of tests per file.
+  //------------------------------------------------------------------------------------------------------------------
+  println!("\nTests per file:");
+  let mut total_per_file = 0;
+  println!("┌────────────────────────────────────────────────────────────────────────┬────────┐");
+  for (name, count) in &ctx.test_case_count_per_file {
+    println!("│ {:70} │ {:6} │", name, count);
+    total_per_file += count;
+  }
+  println!("├────────────────────────────────────────────────────────────────────────┼────────┤");
+  println!("│                                                                  Total │ {:6} │", total_per_file);
+  println!("└────────────────────────────────────────────────────────────────────────┴────────┘");
+  //------------------------------------------------------------------------------------------------------------------
+  // Report execution durations.
+  //------------------------------------------------------------------------------------------------------------------
+  let durations = ctx
+    .test_case_duration
+    .iter()
+    .map(|(key, duration)| (*duration, key.clone()))
+    .collect::<BTreeMap<Duration, (String, String, String)>>();
+  for (d, k) in durations {
+    println!("{:12} µs  {}/{}/{}", d.as_micros(), k.0, k.1, k.2);
   }
+  // Display summary of successful/failed tests
+  ctx.display_tests_summary(cm);
+  // display summary of successful/failed test cases
+  ctx.display_test_cases_summary(cm)?;
+  // write optional reports
+  if let Some(junit_report_file) = &config.junit_report_file {
+    junit::write_junit_report(junit_report_file, &ctx.test_records)?;
+  }
+  if let Some(json_report_file) = &config.json_report_file {
+    json_report::write_json_report(json_report_file, &ctx.test_records)?;
+  }
+  if let Some(html_report_file) = &config.html_report_file {
+    html_report::write_html_report(html_report_file, &ctx.test_records, &ctx.test_case_results())?;
+  }
+  if let Some(computed_dir) = &config.computed_dir {
+    write_computed_files(&ctx, &steps, computed_dir)?;
+  }
+  // display timings summary
+  let total_count = ctx.success_count + ctx.failure_count + ctx.expected_failure_count + ctx.unexpected_success_count + ctx.timeout_count;
+  let requests_per_second = total_count as f64 / run_duration.as_secs_f64();
+  println!("\nTimings:");
+  println!("┌───────────────────────────┬────────┐");
+  println!("│ Average request time [ms] │ {:>6.03} │", (ctx.execution_time as f64) / (total_count as f64) / 1_000_000.0);
+  println!("│       Requests per second │ {:>6.0} │", requests_per_second);
+  println!("│            Worker threads │ {:>6} │", worker_count);
+  println!("└───────────────────────────┴────────┘");
+  // compare results with baseline
+  if let Some(baseline) = &baseline {
+    let comparison = baseline::compare(baseline, &ctx.test_case_results());
+    comparison.display(cm);
+    if let Some(baseline_report_file) = &config.baseline_report_file {
+      comparison.write(baseline_report_file)?;
+    }
+    if !comparison.newly_failing.is_empty() {
+      return Err(RunnerError::NewFailures {
+        count: comparison.newly_failing.len(),
+      });
+    }
+    return Ok(());
+  }
+  if ctx.failure_count + ctx.timeout_count > 0 {
+    return Err(RunnerError::TestsFailed {
+      count: ctx.failure_count + ctx.timeout_count,
+    });
+  }
+  Ok(())
+}
+
+/// Single step of the testing process, steps are reported in the order they were prepared.
+enum Step {
+  /// Failure detected while preparing tests, e.g. invalid model definitions.
+  Failure(String, RunnerError),
+  /// Parsed test file, with an error when parsing the file or preparing its tests failed.
+  TestFile(String, Option<RunnerError>),
+  /// Test to be evaluated.
+  Test(Box<TestJob>),
 }
 
-fn execute_tests(ctx: &mut Context, file_path: &str, client: &Client, evaluate_url: &str, cm: ColorMode) {
-  text_parsing_test_file(cm, file_path).print();
-  let test_cases = parse_test_file(file_path);
-  text_green_ok(cm).cprintln();
+/// Settings applied to tests while they are prepared.
+struct TestSettings {
+  /// Filter selecting tests to be executed.
+  filter: TestFilter,
+  /// Expectations for known failing tests.
+  expectations: Vec<Expectation>,
+  /// Options of comparing actual and expected values.
+  compare_options: CompareOptions,
+  /// Options of comparing values overridden for selected tests.
+  compare_overrides: Vec<CompareOverride>,
+}
+
+/// Parses the test file and appends the steps for executing all its tests.
+fn prepare_tests(ctx: &Context, file_path: String, settings: &TestSettings, steps: &mut Vec<Step>) {
+  match prepare_test_jobs(ctx, &file_path, settings) {
+    Ok(jobs) => {
+      steps.push(Step::TestFile(file_path, None));
+      steps.extend(jobs.into_iter().map(|job| Step::Test(Box::new(job))));
+    }
+    Err(reason) => steps.push(Step::TestFile(file_path, Some(reason))),
+  }
+}
+
+/// Prepares tests for all result nodes defined in the test file.
+fn prepare_test_jobs(ctx: &Context, file_path: &str, settings: &TestSettings) -> Result<Vec<TestJob>, RunnerError> {
+  let test_cases = parse_test_file(file_path)?;
   let empty_id = String::new();
-  let model_file_name = test_cases.model_name.clone().expect("model name not specified in test case");
-  let workspace_name = ctx.get_workspace_name(&model_file_name);
-  let model_namespace = ctx.get_model_rdnn(&model_file_name);
-  let model_name = ctx.get_model_name(&model_file_name);
+  let Some(model_file_name) = test_cases.model_name.clone() else {
+    return Err(RunnerError::TestFile {
+      file: file_path.to_string(),
+      reason: "model name not specified in test file".to_string(),
+    });
+  };
+  let (Some(workspace_name), Some(model_namespace), Some(model_name)) = (
+    ctx.get_workspace_name(&model_file_name),
+    ctx.get_model_rdnn(&model_file_name),
+    ctx.get_model_name(&model_file_name),
+  ) else {
+    return Err(RunnerError::ModelNotFound {
+      file: file_path.to_string(),
+      model: model_file_name,
+    });
+  };
+  let mut jobs = vec![];
   for test_case in &test_cases.test_cases {
     let test_case_id = test_case.id.as_ref().unwrap_or(&empty_id);
     let opt_invocable_name = test_case.invocable_name.as_ref().cloned();
+    let (directory, file, _) = ctx.test_case_key(file_path, test_case_id)?;
+    let expectation = find_expectation(&settings.expectations, &directory, &file, test_case_id);
     for (i, result_node) in test_case.result_nodes.iter().enumerate() {
       let test_id = if i > 0 { format!("{}:{}", test_case_id, i) } else { test_case_id.to_string() };
       let invocable_name = if let Some(invocable_name) = &opt_invocable_name {
@@ -119,7 +329,9 @@ fn execute_tests(ctx: &mut Context, file_path: &str, client: &Client, evaluate_u
       } else {
         result_node.name.clone()
       };
-      text_executing_test_case(cm, &test_id, &model_name, &invocable_name).cprint();
+      if !settings.filter.accepts(test_case_id, &invocable_name, &result_node.name, &test_cases.labels) {
+        continue;
+      }
       let invocable_path = format!(
         "{}{}/{}/{}",
         if workspace_name.is_empty() { "".to_string() } else { format!("{}/", workspace_name) },
@@ -127,165 +339,344 @@ fn execute_tests(ctx: &mut Context, file_path: &str, client: &Client, evaluate_u
         model_name,
         invocable_name
       );
+      let input_values = test_case.input_nodes.iter().map(InputNodeDto::from).collect::<Vec<InputNodeDto>>();
+      // business knowledge models are invoked with arguments instead of input values
+      let (input_values, arguments, error) = match test_case.typ {
+        TestCaseType::BusinessKnowledgeModel => match bkm_arguments(&invocable_name, ctx.get_bkm_parameters(&model_file_name, &invocable_name), input_values) {
+          Ok(arguments) => (vec![], Some(arguments), None),
+          Err(reason) => (vec![], None, Some(reason)),
+        },
+        _ => (input_values, None, None),
+      };
       let params = EvaluateParams {
         invocable_path,
-        input_values: test_case.input_nodes.iter().map(InputNodeDto::from).collect(),
+        invocable_type: test_case.typ.to_string(),
+        input_values,
+        arguments,
       };
-      evaluate_test_case(ctx, file_path, client, evaluate_url, test_case_id, &test_id, &params, &result_node.expected, cm);
+      // decision service with multiple outputs returns a context, result nodes name its entries,
+      // the result of a decision service with a single output is compared as a whole, even when it is a context
+      let output_name = (test_case.typ == TestCaseType::DecisionService && test_case.result_nodes.len() > 1).then(|| result_node.name.clone());
+      jobs.push(TestJob {
+        file_path: file_path.to_string(),
+        test_case_id: test_case_id.to_string(),
+        test_id: test_id.clone(),
+        model_name: model_name.clone(),
+        model_rdnn: model_namespace.clone(),
+        workspace_name: workspace_name.clone(),
+        invocable_name,
+        params,
+        error,
+        output_name,
+        error_result: result_node.error_result,
+        cast: result_node.cast.clone(),
+        expected: result_node.expected.as_ref().map(ValueDto::from),
+        computed: result_node.computed.as_ref().map(ValueDto::from),
+        compare_options: settings.compare_options.for_test(&settings.compare_overrides, &directory, &file, test_case_id),
+        expectation: expectation.cloned(),
+        recording_name: recording_name(&directory, &file, &test_id),
+      });
     }
   }
+  Ok(jobs)
 }
 
-#[allow(clippy::too_many_arguments)]
-fn evaluate_test_case(
-  ctx: &mut Context,
-  file_path: &str,
-  client: &Client,
-  evaluate_url: &str,
-  test_case_id: &str,
-  test_id: &str,
-  params: &EvaluateParams,
-  opt_expected: &Option<Value>,
-  cm: ColorMode,
-) {
-  let execution_start_time = Instant::now();
-  match client.post(evaluate_url).json(&params).send() {
-    Ok(response) => {
-      let execution_duration = execution_start_time.elapsed();
-      ctx.execution_time += execution_duration.as_nanos();
-      match response.json::<ResultDto<OptionalValueDto>>() {
-        Ok(result) => {
-          if let Some(data) = result.data {
-            if let Some(result_dto) = data.value {
-              if let Some(expected) = opt_expected {
-                let expected_dto = ValueDto::from(expected);
-                if result_dto == expected_dto {
-                  ctx.write_line(file_path, test_case_id, test_id, TestResult::Success, DEFAULT_REMARK, execution_duration, cm);
-                } else {
-                  ctx.write_line(file_path, test_case_id, test_id, TestResult::Failure, DIFFERS_REMARK, execution_duration, cm);
-                  let actual_json = serde_json::to_string(&result_dto).unwrap();
-                  let expected_json = serde_json::to_string(&expected_dto).unwrap();
-                  Text::new(cm).nl().s("    result: ").red().s(actual_json.clone()).cprintln();
-                  Text::new(cm).s("  expected: ").green().s(expected_json.clone()).nl().cprintln();
-                  let mut result_chars = actual_json.chars();
-                  let mut expected_chars = expected_json.chars();
-                  let mut index = 0;
-                  while let Some((actual_char, expected_char)) = result_chars.next().zip(expected_chars.next()) {
-                    if actual_char != expected_char {
-                      let pos = if index > 60 { index - 60 } else { 0 };
-                      Text::new(cm)
-                        .s("    actual: ")
-                        .white()
-                        .s(&actual_json[pos..index])
-                        .red()
-                        .s(&actual_json[index..])
-                        .cprintln();
-                      Text::new(cm)
-                        .s("  expected: ")
-                        .white()
-                        .s(&expected_json[pos..index])
-                        .green()
-                        .s(&expected_json[index..])
-                        .nl()
-                        .cprintln();
-                      break;
-                    }
-                    index += 1;
-                  }
-                  // display pretty json comparison
-                  let actual_json_pretty = serde_json::to_string_pretty(&result_dto).unwrap();
-                  let expected_json_pretty = serde_json::to_string_pretty(&expected_dto).unwrap();
-                  let max_width = actual_json_pretty.lines().map(|line| line.len()).max().unwrap();
-                  let mut result_lines = actual_json_pretty.lines();
-                  let mut expected_lines = expected_json_pretty.lines();
-                  println!("  {0:1$} expected:", "actual:", max_width);
-                  while let Some((a, b)) = result_lines.next().zip(expected_lines.next()) {
-                    let color_actual = if a != b { Color::Red } else { Color::White };
-                    let color_expected = if a != b { Color::Green } else { Color::White };
-                    let marker = if a != b { "|" } else { " " };
-                    Text::new(cm)
-                      .yellow()
-                      .s(marker)
-                      .clear()
-                      .space()
-                      .color(color_actual)
-                      .s(format!("{:1$}", a, max_width))
-                      .clear()
-                      .space()
-                      .color(color_expected)
-                      .s(b)
-                      .cprintln();
-                  }
-                  println!();
-                  if ctx.stop_on_failure {
-                    std::process::exit(0);
-                  }
-                }
-              } else {
-                ctx.write_line(file_path, test_case_id, test_id, TestResult::Failure, "no expected value", execution_duration, cm);
-              }
-            } else {
-              ctx.write_line(file_path, test_case_id, test_id, TestResult::Failure, "no actual value", execution_duration, cm);
-            }
-          } else if result.errors.is_some() {
-            ctx.write_line(file_path, test_case_id, test_id, TestResult::Failure, &result.to_string(), execution_duration, cm);
-          } else {
-            ctx.write_line(
-              file_path,
-              test_case_id,
-              test_id,
-              TestResult::Failure,
-              format!("{:?}", result).as_str(),
-              execution_duration,
-              cm,
-            );
-          }
-        }
-        Err(reason) => {
-          ctx.write_line(file_path, test_case_id, test_id, TestResult::Failure, &reason.to_string(), execution_duration, cm);
+/// Lists all prepared tests and failures detected while preparing them,
+/// ends with an error when any model definitions or test files could not be processed.
+fn dry_run(steps: &[Step], dry_run_file: Option<&str>, cm: ColorMode) -> Result<(), RunnerError> {
+  let mut plan = Plan::default();
+  for step in steps {
+    match step {
+      Step::Failure(file_path, reason) | Step::TestFile(file_path, Some(reason)) => plan.add_failure(file_path, reason),
+      Step::TestFile(_, None) => {}
+      Step::Test(job) => {
+        if let Some(error) = &job.error {
+          plan.add_failure(&job.file_path, format!("{}: test {}: {}", job.file_path, job.test_id, error));
         }
+        plan.add_test(job);
       }
     }
-    Err(reason) => {
-      let execution_duration = execution_start_time.elapsed();
-      ctx.execution_time += execution_duration.as_nanos();
-      ctx.write_line(file_path, test_case_id, test_id, TestResult::Failure, &reason.to_string(), execution_duration, cm);
+  }
+  plan.display(cm);
+  if let Some(dry_run_file) = dry_run_file {
+    plan.write(dry_run_file)?;
+  }
+  if !plan.failures.is_empty() {
+    return Err(RunnerError::PreparationFailed { count: plan.failures.len() });
+  }
+  Ok(())
+}
+
+/// Returns named arguments of business knowledge model invocation.
+///
+/// When formal parameters of the business knowledge model are known,
+/// input values not matching any of the parameters are reported as an error.
+fn bkm_arguments(bkm_name: &str, parameters: Option<&[String]>, input_values: Vec<InputNodeDto>) -> Result<ArgumentsDto, String> {
+  if let Some(parameters) = parameters {
+    if let Some(input) = input_values.iter().find(|input| !parameters.contains(&input.name)) {
+      return Err(format!("input '{}' is not a parameter of business knowledge model '{}'", input.name, bkm_name));
     }
   }
+  Ok(ArgumentsDto::Named(input_values))
 }
 
-fn search_files(path: &Path, pattern: &Regex, files: &mut BTreeMap<String, (Vec<String>, Vec<String>)>) {
-  if let Ok(entries) = fs::read_dir(path) {
-    for entry in entries.flatten() {
-      let path = entry.path();
-      if path.is_dir() {
-        search_files(&path, pattern, files);
-      } else if let Some(dir) = path.parent() {
-        let dir_name = dir.canonicalize().unwrap().display().to_string();
-        if let Some(exp) = path.extension() {
-          if exp == "dmn" {
-            let file_name = path.file_name().unwrap().to_string_lossy().to_string();
-            let full_name = format!("{}/{}", dir_name, file_name);
-            if pattern.is_match(&full_name) {
-              let (files_dmn, _) = files.entry(dir_name.clone()).or_insert((vec![], vec![]));
-              files_dmn.push(file_name);
-            }
-          }
-          if exp == "xml" {
-            let file_name = path.file_name().unwrap().to_string_lossy().to_string();
-            let full_name = format!("{}/{}", dir_name, file_name);
-            if pattern.is_match(&full_name) {
-              let (_, files_xml) = files.entry(dir_name).or_insert((vec![], vec![]));
-              files_xml.push(file_name);
-            }
-          }
-        }
+/// Evaluates the test, when the engine managed by the runner can not be reached,
+/// the engine is restarted if it has crashed and the test is evaluated again.
+fn evaluate(client: &Client, evaluate_url: &str, job: &TestJob, retry_policy: RetryPolicy, server: Option<&ManagedServer>) -> Evaluation {
+  let evaluation = executor::evaluate(client, evaluate_url, job, retry_policy);
+  match (&evaluation.response, server) {
+    (Response::Unreachable(_), Some(server)) => match server.restart_if_exited() {
+      Ok(()) => executor::evaluate(client, evaluate_url, job, retry_policy),
+      Err(reason) => Evaluation {
+        response: Response::Unreachable(reason),
+        ..evaluation
+      },
+    },
+    _ => evaluation,
+  }
+}
+
+/// Reports the step of the testing process.
+fn report_step(ctx: &mut Context, step: &Step, evaluation: Option<Evaluation>, record_dir: Option<&str>, cm: ColorMode) -> Result<(), RunnerError> {
+  match (step, evaluation) {
+    (Step::Failure(file_path, reason), _) => ctx.write_line(file_path, "", "", &TestOutcome::failure(reason.to_string()), Duration::ZERO, cm),
+    (Step::TestFile(file_path, None), _) => {
+      text_parsing_test_file(cm, file_path).print();
+      text_green_ok(cm).cprintln();
+      Ok(())
+    }
+    (Step::TestFile(file_path, Some(reason)), _) => {
+      text_parsing_test_file(cm, file_path).print();
+      ctx.write_line(file_path, "", "", &TestOutcome::failure(reason.to_string()), Duration::ZERO, cm)
+    }
+    (Step::Test(job), Some(evaluation)) => evaluate_test_case(ctx, job, evaluation, record_dir, cm),
+    (Step::Test(job), None) => {
+      text_executing_test_case(cm, &job.test_id, &job.model_name, &job.invocable_name).cprint();
+      let outcome = match &job.error {
+        Some(error) => TestOutcome::failure(error),
+        None => TestOutcome::ignored(job.expectation.as_ref().map(|expectation| expectation.reason.as_str()).unwrap_or_default()),
+      };
+      ctx.write_line(&job.file_path, &job.test_case_id, &job.test_id, &with_test_details(outcome, job, None), Duration::ZERO, cm)
+    }
+  }
+}
+
+fn evaluate_test_case(ctx: &mut Context, job: &TestJob, evaluation: Evaluation, record_dir: Option<&str>, cm: ColorMode) -> Result<(), RunnerError> {
+  text_executing_test_case(cm, &job.test_id, &job.model_name, &job.invocable_name).cprint();
+  let execution_duration = evaluation.duration;
+  ctx.execution_time += execution_duration.as_nanos();
+  if let Some(record_dir) = record_dir {
+    let recording = Recording {
+      request: serde_json::to_value(&job.params).unwrap_or_default(),
+      response: evaluation.body.clone(),
+      error: match &evaluation.response {
+        Response::Failed(reason) | Response::TimedOut(reason) => Some(reason.clone()),
+        _ => None,
+      },
+    };
+    write_recording(record_dir, &job.recording_name, &recording)?;
+  }
+  let outcome = match evaluation.response {
+    Response::Received(result) => judge(job, result),
+    Response::Failed(reason) => TestOutcome::failure(reason),
+    Response::TimedOut(reason) => TestOutcome::timeout(reason),
+    Response::Unreachable(reason) => return Err(reason),
+  };
+  let outcome = match &job.expectation {
+    Some(expectation) if expectation.status == ExpectationKind::Xfail => outcome.expected_to_fail(&expectation.reason),
+    _ => outcome,
+  };
+  let outcome = with_test_details(outcome, job, evaluation.body);
+  ctx.write_line(&job.file_path, &job.test_case_id, &job.test_id, &outcome, execution_duration, cm)?;
+  if outcome.result == TestResult::Failure && !outcome.differences.is_empty() {
+    display_differences(&outcome.differences, cm);
+  }
+  Ok(())
+}
+
+/// Compares the result returned by the engine with the result expected by the test.
+fn judge(job: &TestJob, mut result: ResultDto<OptionalValueDto>) -> TestOutcome {
+  if job.error_result {
+    return if result.has_errors() {
+      TestOutcome::success()
+    } else if result.data.as_ref().and_then(|data| data.value.as_ref()).is_some_and(|value| !value.is_null()) {
+      TestOutcome::failure(ERROR_EXPECTED_REMARK)
+    } else {
+      TestOutcome::failure(ERROR_EXPECTED_NULL_REMARK)
+    };
+  }
+  let Some(data) = result.data.take() else {
+    return if result.errors.is_some() {
+      TestOutcome::failure(result.to_string())
+    } else {
+      TestOutcome::failure(format!("{:?}", result))
+    };
+  };
+  let Some(actual) = data.value else {
+    return TestOutcome::failure("no actual value");
+  };
+  let actual = match &job.output_name {
+    Some(output_name) => match output_value(actual, output_name) {
+      Ok(value) => value,
+      Err(reason) => return TestOutcome::failure(reason),
+    },
+    None => actual,
+  };
+  let actual = match &job.cast {
+    Some(cast) => match actual.cast(cast) {
+      Ok(value) => value,
+      Err(reason) => return TestOutcome::failure(format!("casting actual value to '{}' failed: {}", cast, reason)),
+    },
+    None => actual,
+  };
+  let Some(expected) = &job.expected else {
+    return TestOutcome::failure("no expected value");
+  };
+  let differences = diff(&actual, expected, &job.compare_options);
+  if differences.is_empty() {
+    TestOutcome::success().with_actual(actual)
+  } else {
+    TestOutcome::failure(DIFFERS_REMARK).with_actual(actual).with_differences(differences)
+  }
+}
+
+/// Completes the outcome with the expected value, the request prepared for the test
+/// and the response received, so every reported test can be analyzed, also when it was not evaluated.
+fn with_test_details(outcome: TestOutcome, job: &TestJob, response: Option<String>) -> TestOutcome {
+  outcome.with_expected(job.expected.clone()).with_exchange(Exchange {
+    model_name: job.model_name.clone(),
+    invocable_path: job.params.invocable_path.clone(),
+    request: serde_json::to_value(&job.params).unwrap_or_default(),
+    response,
+  })
+}
+
+/// Returns the named output from the result of a decision service,
+/// the result that is not a context is the value of the only output.
+fn output_value(actual: ValueDto, output_name: &str) -> Result<ValueDto, String> {
+  let Some(components) = actual.components else {
+    return Ok(actual);
+  };
+  components
+    .into_iter()
+    .find(|component| component.name.as_deref() == Some(output_name))
+    .map(|component| component.value.unwrap_or_default())
+    .ok_or_else(|| format!("no output '{}' in decision service result", output_name))
+}
+
+/// Displays tests whose computed values differ from expected values.
+fn display_inconsistencies(inconsistencies: &[Inconsistency], cm: ColorMode) {
+  println!("\nComputed values differing from expected values:");
+  if inconsistencies.is_empty() {
+    text_green_ok(cm).cprintln();
+  }
+  for inconsistency in inconsistencies {
+    Text::new(cm)
+      .s("  ")
+      .s(&inconsistency.file_path)
+      .s(" test: ")
+      .yellow()
+      .s(&inconsistency.test_id)
+      .clear()
+      .cprint();
+    display_differences(&inconsistency.differences, cm);
+  }
+}
+
+/// Writes copies of all test files containing executed tests, with actual values stored as computed values.
+///
+/// Stored are the values compared with expected values, i.e. outputs extracted from decision service results
+/// and cast to the type required by the result node, not the raw values returned by the engine.
+/// Copies are placed in the output directory at the same paths relative to their roots,
+/// prefixed with root names when there is more than one root. Files inside test roots are never written.
+fn write_computed_files(ctx: &Context, steps: &[Step], computed_dir: &str) -> Result<(), RunnerError> {
+  let actual_values = ctx
+    .test_records
+    .iter()
+    .filter_map(|record| {
+      record
+        .actual
+        .as_ref()
+        .map(|actual| ((record.directory.as_str(), record.file.as_str(), record.test_id.as_str()), actual))
+    })
+    .collect::<HashMap<_, _>>();
+  let mut files = BTreeMap::<&str, HashMap<String, ValueDto>>::new();
+  for step in steps {
+    if let Step::Test(job) = step {
+      let (directory, file, _) = ctx.test_case_key(&job.file_path, &job.test_case_id)?;
+      if let Some(actual) = actual_values.get(&(directory.as_str(), file.as_str(), job.test_id.as_str())) {
+        files.entry(&job.file_path).or_default().insert(job.test_id.clone(), (*actual).clone());
       }
     }
   }
+  fs::create_dir_all(computed_dir).map_err(|reason| RunnerError::io(computed_dir, reason))?;
+  let output_dir = Path::new(computed_dir).canonicalize().map_err(|reason| RunnerError::io(computed_dir, reason))?;
+  for (file_path, file_values) in files {
+    let root = ctx.root_of(file_path)?;
+    let relative_path = Path::new(file_path).strip_prefix(&root.path).map_err(|reason| RunnerError::io(file_path, reason))?;
+    let output_file = output_dir.join(&root.prefix).join(relative_path);
+    if output_file == Path::new(file_path) || ctx.roots.iter().any(|root| output_file.starts_with(&root.path)) {
+      return Err(RunnerError::Configuration(format!(
+        "writing computed values to '{}' would overwrite files in test directory",
+        output_file.display()
+      )));
+    }
+    computed::write_computed(file_path, &output_file, &file_values)?;
+  }
+  Ok(())
+}
+
+/// Displays structural differences between actual and expected values.
+fn display_differences(differences: &[Difference], cm: ColorMode) {
+  println!();
+  for difference in differences {
+    let note = difference.note.as_ref().map(|note| format!(" ({})", note)).unwrap_or_default();
+    Text::new(cm)
+      .s("    ")
+      .s(&difference.path)
+      .s(": expected ")
+      .green()
+      .s(&difference.expected)
+      .clear()
+      .s(" got ")
+      .red()
+      .s(&difference.actual)
+      .clear()
+      .s(&note)
+      .cprintln();
+  }
+  println!();
 }
 
-/// Displays usage message.
-fn usage() {
-  println!("TBD")
+/// Collects model definitions and test files from the directory and all its subdirectories, grouped by canonical directory name.
+fn search_files(root: &TestRoot, path: &Path, files: &mut BTreeMap<String, (Vec<String>, Vec<String>)>) -> Result<(), RunnerError> {
+  let dir_path = path.display().to_string();
+  let entries = fs::read_dir(path).map_err(|reason| RunnerError::io(&dir_path, reason))?;
+  for entry in entries {
+    let path = entry.map_err(|reason| RunnerError::io(&dir_path, reason))?.path();
+    if path.is_dir() {
+      search_files(root, &path, files)?;
+      continue;
+    }
+    let is_model = match path.extension() {
+      Some(exp) if exp == "dmn" => true,
+      Some(exp) if exp == "xml" => false,
+      _ => continue,
+    };
+    let file_path = path.display().to_string();
+    let (Some(dir), Some(file_name)) = (path.parent(), path.file_name().and_then(|file_name| file_name.to_str())) else {
+      return Err(RunnerError::io(&file_path, "file name is not valid UTF-8"));
+    };
+    let dir_name = dir.canonicalize().map_err(|reason| RunnerError::io(&file_path, reason))?;
+    let dir_name = dir_name.to_str().ok_or_else(|| RunnerError::io(&file_path, "path is not valid UTF-8"))?.to_string();
+    let full_name = format!("{}/{}", dir_name, file_name);
+    if is_model && root.file_filter.is_model_match(Path::new(&full_name)) {
+      let (files_dmn, _) = files.entry(dir_name).or_insert((vec![], vec![]));
+      files_dmn.push(file_name.to_string());
+    } else if !is_model && root.file_filter.is_match(&root.path, Path::new(&full_name)) {
+      let (_, files_xml) = files.entry(dir_name).or_insert((vec![], vec![]));
+      files_xml.push(file_name.to_string());
+    }
+  }
+  Ok(())
 }
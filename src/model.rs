@@ -1,5 +1,6 @@
 //! # XML model for test cases
 
+use crate::errors::RunnerError;
 use roxmltree::Node;
 use std::fmt::Display;
 use std::fs::read_to_string;
@@ -33,7 +34,6 @@ const ATTR_TYPE: &str = "type";
 #[derive(Debug)]
 pub struct TestCases {
   pub model_name: Option<String>,
-  #[allow(dead_code)]
   pub labels: Vec<String>,
   pub test_cases: Vec<TestCase>,
 }
@@ -86,7 +86,6 @@ pub struct TestCase {
   #[allow(dead_code)]
   pub name: Option<String>,
   /// Type of this [TestCase] with default value `Decision`.
-  #[allow(dead_code)]
   pub typ: TestCaseType,
   /// Optional description for this [TestCase].
   #[allow(dead_code)]
@@ -112,14 +111,14 @@ pub struct InputNode {
 #[derive(Debug)]
 pub struct ResultNode {
   pub name: String,
-  #[allow(dead_code)]
+  /// Flag indicating if the evaluation of this [ResultNode] is expected to end with an error.
   pub error_result: bool,
   #[allow(dead_code)]
   pub typ: TestCaseType,
-  #[allow(dead_code)]
+  /// Optional type the actual value should be cast to before comparing with expected value.
   pub cast: Option<String>,
   pub expected: Option<Value>,
-  #[allow(dead_code)]
+  /// Optional value computed by an engine, recorded in the test file.
   pub computed: Option<Value>,
 }
 
@@ -172,52 +171,52 @@ impl Default for List {
 }
 
 /// Parses the XML file containing test cases.
-pub fn parse_test_file(file_name: &str) -> TestCases {
-  let content = read_to_string(file_name).expect("reading test file failed");
-  let document = roxmltree::Document::parse(&content).expect("parsing test file failed");
+pub fn parse_test_file(file_name: &str) -> Result<TestCases, RunnerError> {
+  let content = read_to_string(file_name).map_err(|reason| RunnerError::io(file_name, reason))?;
+  let document = roxmltree::Document::parse(&content).map_err(|reason| RunnerError::xml_document(file_name, reason))?;
   let test_cases_node = document.root_element();
   if test_cases_node.tag_name().name() != NODE_TEST_CASES {
-    panic!("Expected mandatory node: {}", NODE_TEST_CASES);
+    Err(RunnerError::xml(&test_cases_node, format!("expected mandatory node '{}'", NODE_TEST_CASES)).in_file(file_name))
   } else {
-    parse_root_node(&test_cases_node)
+    parse_root_node(&test_cases_node).map_err(|e| e.in_file(file_name))
   }
 }
 
 /// Parses `testCases` node being the root element of the document.
-fn parse_root_node(node: &Node) -> TestCases {
-  TestCases {
-    model_name: optional_child_required_content(node, NODE_MODEL_NAME),
-    labels: parse_labels(node),
-    test_cases: parse_test_cases(node),
-  }
+fn parse_root_node(node: &Node) -> Result<TestCases, RunnerError> {
+  Ok(TestCases {
+    model_name: optional_child_required_content(node, NODE_MODEL_NAME)?,
+    labels: parse_labels(node)?,
+    test_cases: parse_test_cases(node)?,
+  })
 }
 
 /// Parses all labels.
-fn parse_labels(node: &Node) -> Vec<String> {
+fn parse_labels(node: &Node) -> Result<Vec<String>, RunnerError> {
   let mut items = vec![];
   if let Some(labels_node) = node.children().find(|n| n.tag_name().name() == NODE_LABELS) {
     for ref label_node in labels_node.children().filter(|n| n.tag_name().name() == NODE_LABEL) {
-      items.push(required_content(label_node))
+      items.push(required_content(label_node)?)
     }
   }
-  items
+  Ok(items)
 }
 
 /// Parses all test cases.
-fn parse_test_cases(node: &Node) -> Vec<TestCase> {
+fn parse_test_cases(node: &Node) -> Result<Vec<TestCase>, RunnerError> {
   let mut items = vec![];
   for ref test_case_node in node.children().filter(|n| n.tag_name().name() == NODE_TEST_CASE) {
     items.push(TestCase {
       id: optional_attribute(test_case_node, ATTR_ID),
       name: optional_attribute(test_case_node, ATTR_NAME),
       typ: parse_test_case_type(test_case_node),
-      description: optional_child_required_content(test_case_node, NODE_DESCRIPTION),
+      description: optional_child_required_content(test_case_node, NODE_DESCRIPTION)?,
       invocable_name: optional_attribute(test_case_node, ATTR_INVOCABLE_NAME),
-      input_nodes: parse_input_nodes(test_case_node),
-      result_nodes: parse_result_nodes(test_case_node),
+      input_nodes: parse_input_nodes(test_case_node)?,
+      result_nodes: parse_result_nodes(test_case_node)?,
     })
   }
-  items
+  Ok(items)
 }
 
 /// Parses test case type. The default value is [TestCaseType#Decision].
@@ -230,23 +229,23 @@ fn parse_test_case_type(node: &Node) -> TestCaseType {
 }
 
 /// Parses input nodes defined for test case.
-fn parse_input_nodes(node: &Node) -> Vec<InputNode> {
+fn parse_input_nodes(node: &Node) -> Result<Vec<InputNode>, RunnerError> {
   let mut items = vec![];
   for ref input_node in node.children().filter(|n| n.tag_name().name() == NODE_INPUT_NODE) {
     items.push(InputNode {
-      name: required_attribute(input_node, ATTR_NAME),
+      name: required_attribute(input_node, ATTR_NAME)?,
       value: parse_value_type(input_node),
     })
   }
-  items
+  Ok(items)
 }
 
 /// Parses result nodes expected by test case.
-fn parse_result_nodes(node: &Node) -> Vec<ResultNode> {
+fn parse_result_nodes(node: &Node) -> Result<Vec<ResultNode>, RunnerError> {
   let mut items = vec![];
   for ref result_node in node.children().filter(|n| n.tag_name().name() == NODE_RESULT_NODE) {
     items.push(ResultNode {
-      name: required_attribute(result_node, ATTR_NAME),
+      name: required_attribute(result_node, ATTR_NAME)?,
       error_result: optional_attribute(result_node, ATTR_ERROR_RESULT).is_some_and(|v| v == "true"),
       typ: optional_attribute(result_node, ATTR_TYPE).into(),
       cast: optional_attribute(result_node, ATTR_CAST),
@@ -254,7 +253,7 @@ fn parse_result_nodes(node: &Node) -> Vec<ResultNode> {
       computed: parse_child_value_type(result_node, NODE_COMPUTED),
     })
   }
-  items
+  Ok(items)
 }
 
 /// Parses value type.
@@ -333,11 +332,11 @@ fn parse_value_list(node: &Node) -> Option<List> {
 }
 
 /// XML utility function that returns the value of the required attribute or an error.
-fn required_attribute(node: &Node, attr_name: &str) -> String {
+fn required_attribute(node: &Node, attr_name: &str) -> Result<String, RunnerError> {
   node
     .attribute(attr_name)
-    .unwrap_or_else(|| panic!("No mandatory attribute '{}' in node '{}'", attr_name, node.tag_name().name()))
-    .to_string()
+    .map(|attr_value| attr_value.to_string())
+    .ok_or_else(|| RunnerError::xml(node, format!("no mandatory attribute '{}' in node '{}'", attr_name, node.tag_name().name())))
 }
 
 /// XML utility function that returns the value of the optional attribute.
@@ -356,11 +355,11 @@ fn optional_nil_attribute(node: &Node) -> bool {
 }
 
 /// XML utility function that returns required textual content from the specified node.
-fn required_content(node: &Node) -> String {
+fn required_content(node: &Node) -> Result<String, RunnerError> {
   node
     .text()
-    .unwrap_or_else(|| panic!("No mandatory text content in node: '{}'", node.tag_name().name()))
-    .to_string()
+    .map(|text| text.to_string())
+    .ok_or_else(|| RunnerError::xml(node, format!("no mandatory text content in node '{}'", node.tag_name().name())))
 }
 
 /// XML utility function that returns optional textual content of the node.
@@ -369,6 +368,10 @@ fn optional_content(node: &Node) -> Option<String> {
 }
 
 /// XML utility function that returns the required textual content from the optional child node.
-fn optional_child_required_content(node: &Node, child_name: &str) -> Option<String> {
-  node.children().find(|n| n.tag_name().name() == child_name).map(|child_node| required_content(&child_node))
+fn optional_child_required_content(node: &Node, child_name: &str) -> Result<Option<String>, RunnerError> {
+  node
+    .children()
+    .find(|n| n.tag_name().name() == child_name)
+    .map(|child_node| required_content(&child_node))
+    .transpose()
 }
@@ -1,6 +1,6 @@
 //! # Endpoint parameters
 
-use crate::dto::InputNodeDto;
+use crate::dto::{ArgumentsDto, InputNodeDto};
 use serde::Serialize;
 
 /// Parameters for evaluating an invocable.
@@ -9,7 +9,13 @@ pub struct EvaluateParams {
   /// Path to invocable being evaluated.
   #[serde(rename = "invocable")]
   pub invocable_path: String,
+  /// Type of the invocable: `decision`, `bkm` or `decisionService`.
+  #[serde(rename = "type")]
+  pub invocable_type: String,
   /// Input values.
   #[serde(rename = "input")]
   pub input_values: Vec<InputNodeDto>,
+  /// Arguments of the function invocation, passed when a business knowledge model is evaluated.
+  #[serde(rename = "arguments", skip_serializing_if = "Option::is_none")]
+  pub arguments: Option<ArgumentsDto>,
 }
@@ -0,0 +1,34 @@
+//! # Checking the readiness of the engine
+
+use crate::errors::RunnerError;
+use reqwest::blocking::Client;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Delay between consecutive readiness probes.
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Timeout of a single readiness probe.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Polls the engine at the specified URL until it responds or the wait time elapses.
+///
+/// Any HTTP response, regardless of its status, means the engine is ready.
+/// The engine is probed at least once, even when the wait time is zero.
+pub fn wait_for_server(url: &str, wait_time: Duration) -> Result<(), RunnerError> {
+  let unreachable = |reason: String| RunnerError::EngineUnreachable { url: url.to_string(), reason };
+  let client = Client::builder()
+    .timeout(PROBE_TIMEOUT)
+    .build()
+    .map_err(|reason| unreachable(format!("creating HTTP client failed: {}", reason)))?;
+  let start_time = Instant::now();
+  loop {
+    match client.get(url).send() {
+      Ok(_) => return Ok(()),
+      Err(reason) if start_time.elapsed() >= wait_time => {
+        return Err(unreachable(format!("no response within {} s: {}", wait_time.as_secs(), reason)));
+      }
+      Err(_) => thread::sleep(PROBE_INTERVAL),
+    }
+  }
+}
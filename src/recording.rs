@@ -0,0 +1,46 @@
+//! # Recorded evaluation requests and responses
+
+use crate::errors::RunnerError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Request sent to the engine and the raw response received, stored in a single JSON file per test.
+#[derive(Serialize, Deserialize)]
+pub struct Recording {
+  /// Request sent to the engine.
+  pub request: serde_json::Value,
+  /// Raw body of the response, if any was received.
+  pub response: Option<String>,
+  /// Reason of the request failure, if no response was received.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+/// Returns the name of the recording file for the test, relative to recordings directory.
+pub fn recording_name(directory: &str, file: &str, test_id: &str) -> String {
+  let sanitize = |name: &str| name.chars().map(|ch| if ch.is_alphanumeric() || "._-".contains(ch) { ch } else { '_' }).collect::<String>();
+  let directory = directory.split('/').filter(|segment| !segment.is_empty()).map(sanitize).collect::<Vec<String>>();
+  let mut segments = directory;
+  segments.push(sanitize(file));
+  segments.push(format!("{}.json", if test_id.is_empty() { "_".to_string() } else { sanitize(test_id) }));
+  segments.join("/")
+}
+
+/// Writes the recording into the file in recordings directory, creating parent directories if needed.
+pub fn write_recording(dir: &str, name: &str, recording: &Recording) -> Result<(), RunnerError> {
+  let path = Path::new(dir).join(name);
+  let file_name = path.display().to_string();
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).map_err(|reason| RunnerError::io(&file_name, reason))?;
+  }
+  let content = serde_json::to_string_pretty(recording).map_err(|reason| RunnerError::io(&file_name, reason))?;
+  fs::write(&path, content).map_err(|reason| RunnerError::io(&file_name, reason))
+}
+
+/// Reads the recording from the file in recordings directory.
+pub fn read_recording(dir: &str, name: &str) -> Result<Recording, String> {
+  let path = Path::new(dir).join(name);
+  let content = fs::read_to_string(&path).map_err(|reason| format!("reading recorded response '{}' failed: {}", path.display(), reason))?;
+  serde_json::from_str(&content).map_err(|reason| format!("parsing recorded response '{}' failed: {}", path.display(), reason))
+}
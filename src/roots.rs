@@ -0,0 +1,95 @@
+//! # Root directories containing tests
+
+use crate::config::ConfigurationParams;
+use crate::errors::RunnerError;
+use crate::file_filter::FileFilter;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Configuration parameters of a single root directory containing tests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootParams {
+  /// Path to directory containing tests.
+  pub path: String,
+  /// Name identifying the root in reports, defaults to the name of the directory.
+  #[serde(default)]
+  pub name: Option<String>,
+  /// Pattern for matching test file names, overrides the global file search pattern.
+  #[serde(default)]
+  pub pattern: Option<String>,
+  /// Prefix prepended to workspace names of all models in this root.
+  #[serde(default)]
+  pub workspace: Option<String>,
+}
+
+/// Root directory containing tests.
+pub struct TestRoot {
+  /// Name identifying the root.
+  pub name: String,
+  /// Canonical path of the root directory.
+  pub path: PathBuf,
+  /// Prefix of directory names in reports, empty when there is only one root.
+  pub prefix: String,
+  /// Prefix of workspace names, may be empty.
+  pub workspace: String,
+  /// Pattern for matching test file names.
+  pub file_search_pattern: String,
+  /// Filter selecting files in this root.
+  pub file_filter: FileFilter,
+}
+
+/// Creates test roots from configuration parameters.
+///
+/// When no roots are configured, the only root is the directory specified in `test_cases_dir_path`.
+/// When more than one root is configured, directory names in reports are prefixed with root names.
+pub fn build_roots(config: &ConfigurationParams) -> Result<Vec<TestRoot>, RunnerError> {
+  let root_params = if config.roots.is_empty() {
+    if config.test_cases_dir_path.is_empty() {
+      return Err(RunnerError::Configuration("no test directory specified".to_string()));
+    }
+    vec![RootParams {
+      path: config.test_cases_dir_path.clone(),
+      name: None,
+      pattern: None,
+      workspace: None,
+    }]
+  } else {
+    config.roots.clone()
+  };
+  let mut roots: Vec<TestRoot> = vec![];
+  for params in &root_params {
+    let path = Path::new(&params.path)
+      .canonicalize()
+      .map_err(|reason| RunnerError::Configuration(format!("reading test directory '{}' failed: {}", params.path, reason)))?;
+    if !path.is_dir() {
+      return Err(RunnerError::Configuration(format!("'{}' is not a directory", params.path)));
+    }
+    let name = match &params.name {
+      Some(name) => name.trim_matches('/').to_string(),
+      None => path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+    };
+    for root in &roots {
+      if root.name == name {
+        return Err(RunnerError::Configuration(format!("duplicated test root name '{}'", name)));
+      }
+      if root.path.starts_with(&path) || path.starts_with(&root.path) {
+        return Err(RunnerError::Configuration(format!(
+          "test directories '{}' and '{}' overlap",
+          root.path.display(),
+          path.display()
+        )));
+      }
+    }
+    let file_search_pattern = params.pattern.clone().unwrap_or_else(|| config.file_search_pattern.clone());
+    let file_filter = FileFilter::new(&file_search_pattern, &config.include_patterns, &config.exclude_patterns)?;
+    roots.push(TestRoot {
+      prefix: if root_params.len() > 1 { format!("{}/", name) } else { String::new() },
+      name,
+      path,
+      workspace: params.workspace.as_deref().unwrap_or_default().trim_matches('/').to_string(),
+      file_search_pattern,
+      file_filter,
+    });
+  }
+  Ok(roots)
+}
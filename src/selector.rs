@@ -0,0 +1,33 @@
+//! # Selecting tests by directory, file and test case
+
+use serde::{Deserialize, Serialize};
+
+/// Selects tests by directory, test file and test case identifier.
+///
+/// Unspecified criteria match all tests.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Selector {
+  /// Directory relative to tests root directory, matches also all nested directories.
+  #[serde(default)]
+  pub directory: Option<String>,
+  /// Name of the test file, with or without extension.
+  #[serde(default)]
+  pub file: Option<String>,
+  /// Identifier of the test case.
+  #[serde(default)]
+  pub test_case: Option<String>,
+}
+
+impl Selector {
+  /// Returns `true` when the test identified by directory, file name without extension
+  /// and test case identifier is selected.
+  pub fn matches(&self, directory: &str, file: &str, test_case_id: &str) -> bool {
+    let directory_matches = self.directory.as_ref().is_none_or(|selected| {
+      let selected = selected.trim_matches('/');
+      directory == selected || directory.starts_with(&format!("{}/", selected))
+    });
+    let file_matches = self.file.as_ref().is_none_or(|selected| selected == file || selected.strip_suffix(".xml") == Some(file));
+    let test_case_matches = self.test_case.as_ref().is_none_or(|selected| selected == test_case_id);
+    directory_matches && file_matches && test_case_matches
+  }
+}
@@ -0,0 +1,139 @@
+//! # Engine process managed by the runner
+
+use crate::errors::RunnerError;
+use crate::readiness::wait_for_server;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Name of the file containing standard output of the engine.
+const STDOUT_LOG_FILE: &str = "engine-stdout.log";
+
+/// Name of the file containing standard error of the engine.
+const STDERR_LOG_FILE: &str = "engine-stderr.log";
+
+/// Configuration parameters of the engine process started by the runner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerParams {
+  /// Command starting the engine.
+  pub command: String,
+  /// Command arguments.
+  #[serde(default)]
+  pub args: Vec<String>,
+  /// Working directory of the engine process.
+  #[serde(default)]
+  pub working_dir: Option<String>,
+  /// Additional environment variables of the engine process.
+  #[serde(default)]
+  pub env: BTreeMap<String, String>,
+  /// URL polled until the engine responds, defaults to evaluation URL.
+  #[serde(default)]
+  pub readiness_url: Option<String>,
+  /// Time in seconds to wait for the engine to respond after it was started.
+  #[serde(default = "default_startup_time")]
+  pub startup_time: u64,
+  /// Directory where standard output and standard error of the engine are written,
+  /// defaults to the directory of the report file.
+  #[serde(default)]
+  pub log_dir: Option<String>,
+}
+
+/// The engine is given half a minute to start by default.
+fn default_startup_time() -> u64 {
+  30
+}
+
+/// Engine process started by the runner, the process is killed when dropped.
+pub struct ManagedServer {
+  /// Configuration parameters of the engine process.
+  params: ServerParams,
+  /// URL polled until the engine responds.
+  readiness_url: String,
+  /// Directory where engine logs are written.
+  log_dir: PathBuf,
+  /// Running engine process.
+  child: Mutex<Child>,
+}
+
+impl ManagedServer {
+  /// Starts the engine and waits until it responds.
+  pub fn start(params: &ServerParams, evaluate_url: &str, report_file: &str) -> Result<Self, RunnerError> {
+    let log_dir = match &params.log_dir {
+      Some(log_dir) => PathBuf::from(log_dir),
+      None => Path::new(report_file).parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    if !log_dir.as_os_str().is_empty() {
+      fs::create_dir_all(&log_dir).map_err(|reason| RunnerError::io(&log_dir.display().to_string(), reason))?;
+    }
+    let readiness_url = params.readiness_url.clone().unwrap_or_else(|| evaluate_url.to_string());
+    let child = spawn(params, &log_dir)?;
+    let server = Self {
+      params: params.clone(),
+      readiness_url,
+      log_dir,
+      child: Mutex::new(child),
+    };
+    server.wait_until_ready()?;
+    Ok(server)
+  }
+
+  /// Restarts the engine when the process has exited, returns an error when the restart failed.
+  ///
+  /// Calls from multiple threads are serialized, so the crashed engine is restarted only once.
+  pub fn restart_if_exited(&self) -> Result<(), RunnerError> {
+    let mut child = self.child.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match child.try_wait() {
+      Ok(None) => Ok(()),
+      Ok(Some(status)) => {
+        eprintln!("engine exited with {}, restarting", status);
+        *child = spawn(&self.params, &self.log_dir)?;
+        self.wait_until_ready()
+      }
+      Err(reason) => Err(RunnerError::Configuration(format!("checking engine process failed: {}", reason))),
+    }
+  }
+
+  /// Waits until the engine responds.
+  fn wait_until_ready(&self) -> Result<(), RunnerError> {
+    wait_for_server(&self.readiness_url, Duration::from_secs(self.params.startup_time))
+  }
+}
+
+impl Drop for ManagedServer {
+  /// Shuts down the engine process.
+  fn drop(&mut self) {
+    let child = self.child.get_mut().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let _ = child.kill();
+    let _ = child.wait();
+  }
+}
+
+/// Spawns the engine process, standard output and standard error are appended to log files.
+fn spawn(params: &ServerParams, log_dir: &Path) -> Result<Child, RunnerError> {
+  let mut command = Command::new(&params.command);
+  command
+    .args(&params.args)
+    .envs(&params.env)
+    .stdin(Stdio::null())
+    .stdout(log_file(&log_dir.join(STDOUT_LOG_FILE))?)
+    .stderr(log_file(&log_dir.join(STDERR_LOG_FILE))?);
+  if let Some(working_dir) = &params.working_dir {
+    command.current_dir(working_dir);
+  }
+  command
+    .spawn()
+    .map_err(|reason| RunnerError::Configuration(format!("starting engine '{}' failed: {}", params.command, reason)))
+}
+
+/// Opens the log file for appending.
+fn log_file(path: &Path) -> Result<File, RunnerError> {
+  OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(path)
+    .map_err(|reason| RunnerError::io(&path.display().to_string(), reason))
+}
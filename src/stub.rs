@@ -0,0 +1,108 @@
+//! # Embedded stub engine
+//!
+//! Stub engine answers evaluation requests with the values expected by test files,
+//! so the runner can be exercised end to end without a real engine.
+
+use crate::errors::RunnerError;
+use crate::executor::TestJob;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use url::Url;
+
+/// Responses returned by the stub engine, indexed by the request body in canonical JSON form.
+pub type StubResponses = HashMap<String, String>;
+
+/// Builds responses for all prepared tests, based on expected values.
+///
+/// Tests expecting an error result are answered with an error, the first test wins when requests are equal.
+/// Tests of decision service outputs sharing the same request are answered with a context containing all outputs.
+pub fn expected_responses<'a>(jobs: impl Iterator<Item = &'a TestJob>) -> StubResponses {
+  let mut responses = HashMap::<String, Value>::new();
+  for job in jobs {
+    let Ok(request) = serde_json::to_value(&job.params) else {
+      continue;
+    };
+    let response = responses.entry(request.to_string()).or_insert_with(|| {
+      if job.error_result {
+        json!({ "errors": [{ "detail": "expected error result" }] })
+      } else if job.output_name.is_some() {
+        json!({ "data": { "value": { "components": [] } } })
+      } else {
+        json!({ "data": { "value": job.expected } })
+      }
+    });
+    if let (Some(output_name), Some(Value::Array(components))) = (&job.output_name, response.pointer_mut("/data/value/components")) {
+      components.push(json!({ "name": output_name, "value": job.expected, "isNil": false }));
+    }
+  }
+  responses.into_iter().map(|(request, response)| (request, response.to_string())).collect()
+}
+
+/// Starts the stub engine listening on the host and port of the evaluation URL.
+///
+/// The engine runs in background threads until the runner exits.
+pub fn start(evaluate_url: &str, responses: StubResponses) -> Result<(), RunnerError> {
+  let url = Url::parse(evaluate_url).map_err(|reason| RunnerError::Configuration(format!("invalid evaluation URL '{}': {}", evaluate_url, reason)))?;
+  let address = format!("{}:{}", url.host_str().unwrap_or("127.0.0.1"), url.port_or_known_default().unwrap_or(80));
+  let listener = TcpListener::bind(&address).map_err(|reason| RunnerError::Configuration(format!("starting stub engine at {} failed: {}", address, reason)))?;
+  let responses = Arc::new(responses);
+  thread::spawn(move || {
+    for stream in listener.incoming().flatten() {
+      let responses = Arc::clone(&responses);
+      thread::spawn(move || {
+        let _ = handle_connection(stream, &responses);
+      });
+    }
+  });
+  Ok(())
+}
+
+/// Reads a single HTTP request and writes the response, the connection is closed afterwards.
+fn handle_connection(stream: TcpStream, responses: &StubResponses) -> std::io::Result<()> {
+  let mut reader = BufReader::new(stream.try_clone()?);
+  let mut request_line = String::new();
+  reader.read_line(&mut request_line)?;
+  let mut content_length = 0;
+  loop {
+    let mut header = String::new();
+    if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+      break;
+    }
+    if let Some((name, value)) = header.split_once(':') {
+      if name.trim().eq_ignore_ascii_case("content-length") {
+        content_length = value.trim().parse().unwrap_or(0);
+      }
+    }
+  }
+  let mut body = vec![0; content_length];
+  reader.read_exact(&mut body)?;
+  let (status, content) = if request_line.starts_with("POST ") {
+    ("200 OK", respond(&body, responses))
+  } else {
+    (
+      "405 Method Not Allowed",
+      json!({ "errors": [{ "detail": "only POST requests are supported" }] }).to_string(),
+    )
+  };
+  let mut stream = stream;
+  write!(
+    stream,
+    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+    status,
+    content.len(),
+    content
+  )?;
+  stream.flush()
+}
+
+/// Returns the response for the request body.
+fn respond(body: &[u8], responses: &StubResponses) -> String {
+  serde_json::from_slice::<serde_json::Value>(body)
+    .ok()
+    .and_then(|request| responses.get(&request.to_string()).cloned())
+    .unwrap_or_else(|| json!({ "errors": [{ "detail": "no response recorded for this request" }] }).to_string())
+}
@@ -0,0 +1,319 @@
+//! # Semantic comparison of XSD temporal values
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// Number of seconds in a day.
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Time zone of a temporal value.
+#[derive(Debug, PartialEq)]
+enum Zone {
+  /// No time zone specified.
+  Local,
+  /// Offset from UTC in seconds, UTC zone identifiers are converted to zero offset.
+  Offset(i64),
+  /// Time zone identifier, like `Europe/Paris`.
+  Id(String),
+}
+
+/// Returns `Some(true)` when both texts represent equal values of the specified temporal type,
+/// `Some(false)` when they differ and `None` when the type is not temporal or any of the texts is invalid.
+pub fn temporal_eq(typ: &str, a: &str, b: &str) -> Option<bool> {
+  match typ {
+    "xsd:date" => Some(instant(parse_date_time(a, true, false)?)? == instant(parse_date_time(b, true, false)?)?),
+    "xsd:time" => {
+      let (a, b) = (parse_date_time(a, false, true)?, parse_date_time(b, false, true)?);
+      // times with offsets are compared as times of day in UTC
+      Some(match (&a.zone, &b.zone) {
+        (Zone::Offset(_), Zone::Offset(_)) => time_of_day(instant(a)?) == time_of_day(instant(b)?),
+        _ => instant(a)? == instant(b)?,
+      })
+    }
+    "xsd:dateTime" => Some(instant(parse_date_time(a, true, true)?)? == instant(parse_date_time(b, true, true)?)?),
+    _ if is_duration_type(typ) => Some(parse_duration(a)? == parse_duration(b)?),
+    _ => None,
+  }
+}
+
+/// Returns `true` when the type is any of XSD duration types.
+pub fn is_duration_type(typ: &str) -> bool {
+  matches!(typ, "xsd:duration" | "xsd:yearMonthDuration" | "xsd:dayTimeDuration")
+}
+
+/// Date and time parsed from text, missing parts are zero.
+struct DateTime {
+  /// Days since 1970-01-01.
+  days: i64,
+  /// Seconds since midnight, including fraction.
+  seconds: Decimal,
+  /// Time zone.
+  zone: Zone,
+}
+
+/// Returns the point in time represented by the value, with its time zone.
+///
+/// Values with offsets are converted to UTC, so equal instants in different offsets are equal.
+/// Returns `None` when the instant is out of range.
+fn instant(value: DateTime) -> Option<(Decimal, Zone)> {
+  let local = Decimal::from(value.days.checked_mul(SECONDS_PER_DAY)?).checked_add(value.seconds)?;
+  Some(match value.zone {
+    Zone::Offset(offset) => (local.checked_sub(Decimal::from(offset))?, Zone::Offset(0)),
+    zone => (local, zone),
+  })
+}
+
+/// Returns the instant reduced to the time of day.
+fn time_of_day((seconds, zone): (Decimal, Zone)) -> (Decimal, Zone) {
+  let day = Decimal::from(SECONDS_PER_DAY);
+  (((seconds % day) + day) % day, zone)
+}
+
+/// Parses date (`-?YYYY-MM-DD`), time (`hh:mm:ss(.f+)?`) or both separated with `T`, followed by optional time zone.
+fn parse_date_time(text: &str, has_date: bool, has_time: bool) -> Option<DateTime> {
+  let text = text.trim();
+  let (mut days, mut rest) = (0, text);
+  if has_date {
+    let (negative, unsigned) = match rest.strip_prefix('-') {
+      Some(unsigned) => (true, unsigned),
+      None => (false, rest),
+    };
+    let year_end = unsigned.find('-')?;
+    let year = unsigned[..year_end].parse::<i64>().ok()?;
+    let year = if negative { -year } else { year };
+    let month = unsigned.get(year_end + 1..year_end + 3)?.parse::<i64>().ok()?;
+    let day = unsigned.get(year_end + 4..year_end + 6)?.parse::<i64>().ok()?;
+    if unsigned.get(year_end + 3..year_end + 4) != Some("-") || !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+      return None;
+    }
+    days = days_from_civil(year, month, day)?;
+    rest = &unsigned[year_end + 6..];
+  }
+  let mut seconds = Decimal::ZERO;
+  if has_time {
+    if has_date {
+      rest = rest.strip_prefix('T')?;
+    }
+    let zone_start = rest.find(['Z', '+', '-', '@']).unwrap_or(rest.len());
+    let time = &rest[..zone_start];
+    let mut parts = time.split(':');
+    let hours = parts.next()?.parse::<i64>().ok()?;
+    let minutes = parts.next()?.parse::<i64>().ok()?;
+    let secs = Decimal::from_str(parts.next()?).ok()?;
+    if parts.next().is_some() || hours > 24 || minutes > 59 || secs >= Decimal::from(60) {
+      return None;
+    }
+    // hour 24 is allowed only as the end of the day
+    if hours == 24 && (minutes > 0 || !secs.is_zero()) {
+      return None;
+    }
+    // time 24:00:00 is the same value as 00:00:00, only a date is moved to the next day
+    seconds = if hours == 24 && !has_date {
+      Decimal::ZERO
+    } else {
+      Decimal::from(hours * 3600 + minutes * 60) + secs
+    };
+    rest = &rest[zone_start..];
+  }
+  Some(DateTime {
+    days,
+    seconds,
+    zone: parse_zone(rest)?,
+  })
+}
+
+/// Parses the time zone, `Z`, `±hh:mm` or `@zone-id`, offsets are limited to ±14:00.
+fn parse_zone(text: &str) -> Option<Zone> {
+  match text {
+    "" => Some(Zone::Local),
+    "Z" | "@UTC" | "@Etc/UTC" | "@GMT" | "@Etc/GMT" | "@Z" => Some(Zone::Offset(0)),
+    _ if text.starts_with('@') => Some(Zone::Id(text[1..].to_string())),
+    _ => {
+      let sign = match text.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+      };
+      let (hours, minutes) = text[1..].split_once(':')?;
+      let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+      if !(0..=14).contains(&hours) || !(0..=59).contains(&minutes) || (hours == 14 && minutes > 0) {
+        return None;
+      }
+      Some(Zone::Offset(sign * (hours * 3600 + minutes * 60)))
+    }
+  }
+}
+
+/// Returns the number of days in the month of the specified year in proleptic Gregorian calendar.
+fn days_in_month(year: i64, month: i64) -> i64 {
+  match month {
+    2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+/// Returns the number of days since 1970-01-01 for the date in proleptic Gregorian calendar,
+/// or `None` when the number of days is out of range.
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let year_of_era = year - era * 400;
+  let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+  let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  era.checked_mul(146_097)?.checked_add(day_of_era - 719_468)
+}
+
+/// Parses duration `-?PnYnMnWnDTnHnMnS` into the total number of months and seconds,
+/// returns `None` when the duration is invalid or out of range.
+fn parse_duration(text: &str) -> Option<(i64, Decimal)> {
+  let text = text.trim();
+  let (negative, text) = match text.strip_prefix('-') {
+    Some(text) => (true, text),
+    None => (false, text),
+  };
+  let text = text.strip_prefix('P')?;
+  let (date_part, time_part) = match text.split_once('T') {
+    Some((date_part, time_part)) if !time_part.is_empty() => (date_part, Some(time_part)),
+    Some(_) => return None,
+    None => (text, None),
+  };
+  let date_parts = duration_parts(date_part)?;
+  let time_parts = duration_parts(time_part.unwrap_or_default())?;
+  if date_parts.is_empty() && time_parts.is_empty() {
+    return None;
+  }
+  let (mut months, mut seconds) = (0_i64, Decimal::ZERO);
+  for (value, designator) in date_parts {
+    if !value.fract().is_zero() {
+      return None;
+    }
+    let value = value.to_i64()?;
+    match designator {
+      'Y' => months = months.checked_add(value.checked_mul(12)?)?,
+      'M' => months = months.checked_add(value)?,
+      'W' => seconds = seconds.checked_add(Decimal::from(value.checked_mul(7 * SECONDS_PER_DAY)?))?,
+      'D' => seconds = seconds.checked_add(Decimal::from(value.checked_mul(SECONDS_PER_DAY)?))?,
+      _ => return None,
+    }
+  }
+  for (value, designator) in time_parts {
+    let value = match designator {
+      'H' => value.checked_mul(Decimal::from(3600))?,
+      'M' => value.checked_mul(Decimal::from(60))?,
+      'S' => value,
+      _ => return None,
+    };
+    seconds = seconds.checked_add(value)?;
+  }
+  Some(if negative { (-months, -seconds) } else { (months, seconds) })
+}
+
+/// Splits the part of duration into numbers followed by designators.
+fn duration_parts(text: &str) -> Option<Vec<(Decimal, char)>> {
+  let mut parts = vec![];
+  let mut number = String::new();
+  for ch in text.chars() {
+    if ch.is_ascii_digit() || ch == '.' {
+      number.push(ch);
+    } else {
+      parts.push((Decimal::from_str(&number).ok()?, ch));
+      number.clear();
+    }
+  }
+  number.is_empty().then_some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn utc_zones_are_equivalent() {
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T10:00:00Z", "2020-01-01T10:00:00+00:00"), Some(true));
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T10:00:00Z", "2020-01-01T10:00:00@Etc/UTC"), Some(true));
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T10:00:00-00:00", "2020-01-01T10:00:00@UTC"), Some(true));
+    assert_eq!(temporal_eq("xsd:time", "10:00:00Z", "10:00:00@Etc/UTC"), Some(true));
+  }
+
+  #[test]
+  fn offsets_are_compared_as_instants() {
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T12:00:00+02:00", "2020-01-01T10:00:00Z"), Some(true));
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T12:00:00+02:00", "2020-01-01T12:00:00Z"), Some(false));
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T10:00:00", "2020-01-01T10:00:00Z"), Some(false));
+    assert_eq!(
+      temporal_eq("xsd:dateTime", "2020-01-01T10:00:00@Europe/Paris", "2020-01-01T10:00:00@Europe/Paris"),
+      Some(true)
+    );
+    assert_eq!(
+      temporal_eq("xsd:dateTime", "2020-01-01T10:00:00@Europe/Paris", "2020-01-01T10:00:00@Europe/Warsaw"),
+      Some(false)
+    );
+  }
+
+  #[test]
+  fn times_with_offsets_wrap_around_midnight() {
+    assert_eq!(temporal_eq("xsd:time", "23:00:00-02:00", "01:00:00Z"), Some(true));
+    assert_eq!(temporal_eq("xsd:time", "01:00:00+02:00", "23:00:00+00:00"), Some(true));
+    assert_eq!(temporal_eq("xsd:time", "10:00:00.000", "10:00:00"), Some(true));
+  }
+
+  #[test]
+  fn durations_are_normalized() {
+    assert_eq!(temporal_eq("xsd:duration", "P1Y", "P12M"), Some(true));
+    assert_eq!(temporal_eq("xsd:yearMonthDuration", "P1Y2M", "P14M"), Some(true));
+    assert_eq!(temporal_eq("xsd:dayTimeDuration", "P1DT1H", "PT25H"), Some(true));
+    assert_eq!(temporal_eq("xsd:dayTimeDuration", "PT90M", "PT1H30M"), Some(true));
+    assert_eq!(temporal_eq("xsd:duration", "P1W", "P7D"), Some(true));
+    assert_eq!(temporal_eq("xsd:duration", "-P1D", "-PT24H"), Some(true));
+    assert_eq!(temporal_eq("xsd:duration", "P1M", "P30D"), Some(false));
+    assert_eq!(temporal_eq("xsd:duration", "P0D", "PT0S"), Some(true));
+  }
+
+  #[test]
+  fn invalid_dates_are_rejected() {
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-02-30T00:00:00", "2020-03-01T00:00:00"), None);
+    assert_eq!(temporal_eq("xsd:date", "2021-02-29", "2021-03-01"), None);
+    assert_eq!(temporal_eq("xsd:date", "2020-02-29", "2020-02-29"), Some(true));
+    assert_eq!(temporal_eq("xsd:date", "2000-02-29", "2000-02-29"), Some(true));
+    assert_eq!(temporal_eq("xsd:date", "1900-02-29", "1900-02-29"), None);
+    assert_eq!(temporal_eq("xsd:date", "2020-04-31", "2020-05-01"), None);
+  }
+
+  #[test]
+  fn hour_24_is_allowed_only_at_the_end_of_day() {
+    assert_eq!(temporal_eq("xsd:time", "24:00:00", "24:00:00"), Some(true));
+    assert_eq!(temporal_eq("xsd:time", "24:00:00", "00:00:00"), Some(true));
+    assert_eq!(temporal_eq("xsd:time", "24:00:00Z", "00:00:00+00:00"), Some(true));
+    assert_eq!(temporal_eq("xsd:time", "24:30:00", "00:30:00"), None);
+    assert_eq!(temporal_eq("xsd:time", "24:00:01", "00:00:01"), None);
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T24:00:00", "2020-01-02T00:00:00"), Some(true));
+  }
+
+  #[test]
+  fn empty_durations_are_rejected() {
+    assert_eq!(temporal_eq("xsd:duration", "P", "P0D"), None);
+    assert_eq!(temporal_eq("xsd:duration", "PT", "PT0S"), None);
+    assert_eq!(temporal_eq("xsd:duration", "-P", "P0D"), None);
+  }
+
+  #[test]
+  fn offsets_are_limited() {
+    assert_eq!(temporal_eq("xsd:time", "10:00:00+14:00", "20:00:00-00:00"), Some(true));
+    assert_eq!(temporal_eq("xsd:time", "10:00:00+14:01", "20:01:00Z"), None);
+    assert_eq!(temporal_eq("xsd:time", "10:00:00+15:00", "19:00:00Z"), None);
+    assert_eq!(temporal_eq("xsd:time", "10:00:00-10:60", "20:00:00Z"), None);
+    assert_eq!(temporal_eq("xsd:dateTime", "2020-01-01T10:00:00+9999999999999999:00", "2020-01-01T10:00:00Z"), None);
+  }
+
+  #[test]
+  fn values_out_of_range_are_rejected() {
+    assert_eq!(temporal_eq("xsd:duration", "P999999999999999999Y", "P1Y"), None);
+    assert_eq!(temporal_eq("xsd:duration", "P999999999999999999W", "P1W"), None);
+    assert_eq!(temporal_eq("xsd:duration", "P999999999999999999D", "P1D"), None);
+    assert_eq!(temporal_eq("xsd:duration", "PT99999999999999999999999999H", "PT1H"), None);
+    assert_eq!(temporal_eq("xsd:date", "999999999999999999-01-01", "2020-01-01"), None);
+  }
+}
@@ -0,0 +1,90 @@
+//! # Running tests against the stub engine
+
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the port not used by any other listener.
+fn free_port() -> u16 {
+  TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+/// Returns the empty directory for report files.
+fn output_dir() -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("dsntk-test-runner-{}", std::process::id()));
+  let _ = fs::remove_dir_all(&dir);
+  fs::create_dir_all(&dir).unwrap();
+  dir
+}
+
+/// Returns the content of the report file.
+fn read(dir: &Path, file_name: &str) -> String {
+  fs::read_to_string(dir.join(file_name)).unwrap()
+}
+
+#[test]
+fn stub_run_writes_all_reports() {
+  let dir = output_dir();
+  let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+  let output = Command::new(env!("CARGO_BIN_EXE_dsntk-test-runner"))
+    .arg("--stub")
+    .arg(format!("--url=http://127.0.0.1:{}/tck", free_port()))
+    .arg("--dir")
+    .arg(&fixtures)
+    .arg("--report")
+    .arg(dir.join("report.csv"))
+    .arg("--tck-report")
+    .arg(dir.join("tck.csv"))
+    .arg("--junit-report")
+    .arg(dir.join("junit.xml"))
+    .arg("--json-report")
+    .arg(dir.join("report.json"))
+    .output()
+    .unwrap();
+  // one of the tests fails, so the runner exits with code 1
+  assert_eq!(Some(1), output.status.code(), "{}", String::from_utf8_lossy(&output.stdout));
+
+  let report = read(&dir, "report.csv");
+  let lines = report.lines().collect::<Vec<&str>>();
+  assert_eq!(
+    vec![
+      r#""compliance-level-2/0001-greeting","0001-greeting-test-01","001","SUCCESS","""#,
+      r#""compliance-level-2/0001-greeting","0001-greeting-test-01","002","SUCCESS","""#,
+      r#""compliance-level-2/0001-greeting","0001-greeting-test-01","003","ERROR","input 'Name' is not a parameter of business knowledge model 'Join'""#,
+    ],
+    lines
+  );
+
+  let tck_report = read(&dir, "tck.csv");
+  let lines = tck_report.lines().collect::<Vec<&str>>();
+  assert_eq!(
+    vec![
+      r#""compliance-level-2/0001-greeting","0001-greeting-test-01","001","SUCCESS","""#,
+      r#""compliance-level-2/0001-greeting","0001-greeting-test-01","002","SUCCESS","""#,
+      r#""compliance-level-2/0001-greeting","0001-greeting-test-01","003","IGNORED","""#,
+    ],
+    lines
+  );
+
+  let junit_report = read(&dir, "junit.xml");
+  assert!(junit_report.contains(r#"<testsuites name="DMN TCK" tests="3" failures="1" skipped="0""#));
+  assert!(junit_report.contains(r#"<testcase classname="compliance-level-2/0001-greeting.0001-greeting-test-01" name="001""#));
+  assert!(junit_report.contains(r#"<failure message="input 'Name' is not a parameter of business knowledge model 'Join'" type="failure">"#));
+
+  let json_report = serde_json::from_str::<serde_json::Value>(&read(&dir, "report.json")).unwrap();
+  assert_eq!(3, json_report["total"]);
+  assert_eq!(2, json_report["success"]);
+  assert_eq!(1, json_report["failure"]);
+  let tests = json_report["tests"].as_array().unwrap();
+  assert_eq!(3, tests.len());
+  assert_eq!("SUCCESS", tests[0]["result"]);
+  assert_eq!("Hello John Doe", tests[0]["actual"]["simple"]["text"]);
+  assert_eq!("First", tests[1]["request"]["arguments"]["named"][0]["name"]);
+  assert_eq!(serde_json::json!([]), tests[1]["request"]["input"]);
+  assert_eq!("ERROR", tests[2]["result"]);
+  assert_eq!("0001-greeting", tests[2]["modelName"]);
+  assert_eq!("John", tests[2]["expected"]["simple"]["text"]);
+
+  fs::remove_dir_all(&dir).unwrap();
+}